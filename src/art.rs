@@ -0,0 +1,106 @@
+//! Accent colors derived from album art
+
+use std::{fs, process::Command, sync::Mutex};
+
+use crate::{color::Color, process};
+
+/// The dominant colors of album art, cached for the current track so the art is only fetched
+/// and decoded once per song
+#[derive(Default)]
+pub struct Accents {
+	/// The art URL of the current track and its dominant color, if it has one
+	last: Mutex<Option<(String, Option<Color>)>>,
+}
+
+impl Accents {
+	/// Creates the cache. Returns `None`, with a warning, if the crate was built without album
+	/// art support, so no art is fetched.
+	pub fn new() -> Option<Self> {
+		if cfg!(not(feature = "album-art")) {
+			eprintln!("warning: album art accents need the album-art feature, which is disabled");
+			return None;
+		}
+		Some(Accents::default())
+	}
+
+	/// The dominant color of the art at `url`, or `None` if it couldn't be fetched or is all
+	/// gray
+	pub fn get(&self, url: &str) -> Option<Color> {
+		let mut last = self.last.lock().unwrap();
+		match &*last {
+			Some((cached, color)) if cached == url => color.clone(),
+			_ => {
+				let color = fetch(url).and_then(|bytes| dominant(&bytes));
+				*last = Some((url.to_owned(), color.clone()));
+				color
+			}
+		}
+	}
+}
+
+/// Reads the art at a `file://` URL, or downloads it with curl
+fn fetch(url: &str) -> Option<Vec<u8>> {
+	if let Some(path) = url.strip_prefix("file://") {
+		return fs::read(percent_decode(path)).ok();
+	}
+	if !url.starts_with("http://") && !url.starts_with("https://") {
+		return None;
+	}
+	let mut curl = Command::new("curl");
+	curl.args(&["--silent", "--fail", "--max-time", "2", url]);
+	let output = process::output(&mut curl).ok()?;
+	Some(output.stdout).filter(|_| output.status.success())
+}
+
+/// Decodes the `%XX` escapes in a URL path
+fn percent_decode(path: &str) -> String {
+	let bytes = path.as_bytes();
+	let mut decoded = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+	while i < bytes.len() {
+		let escape = bytes
+			.get(i + 1..i + 3)
+			.and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+		match (bytes[i], escape) {
+			(b'%', Some(byte)) => {
+				decoded.push(byte);
+				i += 3;
+			}
+			(byte, _) => {
+				decoded.push(byte);
+				i += 1;
+			}
+		}
+	}
+	String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// The most common color of an image, averaged over similar shades. Grays are left out, as
+/// they make for dull accents.
+#[cfg(feature = "album-art")]
+fn dominant(bytes: &[u8]) -> Option<Color> {
+	use std::collections::HashMap;
+
+	let image = image::load_from_memory(bytes).ok()?.thumbnail(32, 32).to_rgb8();
+	// Pixel counts and channel sums, by shade
+	let mut shades: HashMap<[u8; 3], (u32, [u32; 3])> = HashMap::new();
+	for pixel in image.pixels() {
+		let [r, g, b] = pixel.0;
+		let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+		if max - min < 32 {
+			continue;
+		}
+		let (count, sums) = shades.entry([r >> 4, g >> 4, b >> 4]).or_default();
+		*count += 1;
+		for (sum, channel) in sums.iter_mut().zip(&[r, g, b]) {
+			*sum += u32::from(*channel);
+		}
+	}
+	let (count, [r, g, b]) = shades.values().max_by_key(|(count, _)| *count)?;
+	Some(Color::rgb((r / count) as u8, (g / count) as u8, (b / count) as u8))
+}
+
+#[cfg(not(feature = "album-art"))]
+fn dominant(_bytes: &[u8]) -> Option<Color> {
+	None
+}
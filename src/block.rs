@@ -0,0 +1,287 @@
+use std::{
+	sync::{atomic::Ordering, Arc, Mutex},
+	time::{Duration, Instant},
+};
+
+use unixbar::format::{ClickAction, Format, MouseButton};
+
+use crate::{
+	color::Color,
+	config::{Align, BlockConfig, Config, IconStyle},
+	icons::Icon,
+	layout::Layout,
+	watchdog::Heartbeat,
+	widgets::Compact,
+};
+
+/// When a widget updates, and where it reports its updates
+#[derive(Debug, Clone)]
+pub struct Schedule {
+	/// How long the widget waits before its first update
+	pub phase: Duration,
+	pub heartbeat: Heartbeat,
+}
+
+/// The block settings of a widget, and its place in the bar's layout
+#[derive(Debug, Clone)]
+pub struct Block {
+	/// The name of the widget
+	name: String,
+	config: BlockConfig,
+	compact: Compact,
+	layout: Arc<Layout>,
+	slot: usize,
+	phase: Duration,
+	heartbeat: Heartbeat,
+	icons: IconStyle,
+	/// How long updates may fail before the error is shown
+	grace: Duration,
+	/// Shown while updates fail for less than `grace`
+	placeholder: String,
+	/// Why the updates of the widget have been failing, if they have
+	error: Arc<Mutex<Option<Failure>>>,
+}
+
+#[derive(Debug)]
+struct Failure {
+	message: String,
+	/// When the updates started failing
+	since: Instant,
+	/// Whether the error has been shown
+	shown: bool,
+}
+
+/// How long the updates of a widget have been failing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outage {
+	/// For less than the grace period, e.g. while a sensor comes back after a resume
+	Brief,
+	/// For longer than the grace period, with an error that hasn't been shown yet
+	New,
+	/// For longer than the grace period, with the error already shown
+	Ongoing,
+}
+
+impl Block {
+	/// Adds the widget `name` to `layout`, with its block settings from `config`. The widget's
+	/// updates are offset by `phase`, unless the settings override it, and reported to
+	/// `heartbeat`.
+	pub fn new(
+		name: &str,
+		config: &Config,
+		compact: Compact,
+		layout: Arc<Layout>,
+		phase: Duration,
+		heartbeat: Heartbeat,
+	) -> Self {
+		let block = config.blocks.get(name).cloned().unwrap_or_default();
+		let slot = layout.add(block.priority);
+		let phase = block.phase_ms.map_or(phase, Duration::from_millis);
+		Block {
+			name: name.to_owned(),
+			config: block,
+			compact,
+			layout,
+			slot,
+			phase,
+			heartbeat,
+			icons: config.icons,
+			grace: Duration::from_secs(config.unavailable_secs),
+			placeholder: config.unavailable_placeholder.clone(),
+			error: Arc::default(),
+		}
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn config(&self) -> &BlockConfig {
+		&self.config
+	}
+
+	/// Why the last update of the widget failed, or `None` if it succeeded
+	pub fn error(&self) -> Option<String> {
+		self.error.lock().unwrap().as_ref().map(|failure| failure.message.clone())
+	}
+
+	/// Records a successful update
+	pub fn recover(&self) {
+		*self.error.lock().unwrap() = None;
+	}
+
+	/// Records a failed update. Returns how long updates have been failing.
+	pub fn fail(&self, message: String) -> Outage {
+		let mut error = self.error.lock().unwrap();
+		let since = error.as_ref().map_or_else(Instant::now, |failure| failure.since);
+		if since.elapsed() < self.grace {
+			*error = Some(Failure { message, since, shown: false });
+			return Outage::Brief;
+		}
+		let shown = matches!(&*error, Some(failure) if failure.shown && failure.message == message);
+		*error = Some(Failure { message, since, shown: true });
+		match shown {
+			true => Outage::Ongoing,
+			false => Outage::New,
+		}
+	}
+
+	/// What's shown while updates fail for less than the grace period
+	pub fn placeholder(&self) -> &str {
+		&self.placeholder
+	}
+
+	/// The name of the function showing the widget's error
+	pub fn error_fn(&self) -> String {
+		format!("{}_error", self.name)
+	}
+
+	/// How `icon` is drawn in the configured icon style
+	pub fn icon(&self, icon: Icon) -> &'static str {
+		self.icons.pick(icon)
+	}
+
+	/// How the icons of the widget are drawn
+	pub fn icon_style(&self) -> IconStyle {
+		self.icons
+	}
+
+	/// When the widget updates
+	pub fn schedule(&self) -> Schedule {
+		Schedule { phase: self.phase, heartbeat: self.heartbeat.clone() }
+	}
+
+	/// Applies the block settings to a widget's output
+	pub fn apply(&self, format: Format) -> Format {
+		self.apply_with(&self.config, format)
+	}
+
+	/// Applies `config` to a widget's output in place of its own block settings
+	pub fn apply_with(&self, config: &BlockConfig, format: Format) -> Format {
+		let format = config.apply(format);
+		self.layout.show(self.slot, width(&format));
+		format
+	}
+
+	/// Whether the widget should show its compact variant, given the widths of its full and
+	/// compact variants. This is the case in compact mode, or when the bar is too wide.
+	pub fn compact(&self, full: usize, compact: usize) -> bool {
+		let squeezed = self.layout.fit(self.slot, full, compact);
+		self.compact.load(Ordering::Relaxed) || squeezed
+	}
+}
+
+impl BlockConfig {
+	/// Applies the block settings to a widget's output
+	pub fn apply(&self, format: Format) -> Format {
+		let format = match self.min_width {
+			Some(min_width) => pad(format, min_width, self.align),
+			None => format,
+		};
+		let commands = [
+			(MouseButton::ScrollUp, &self.scroll_up),
+			(MouseButton::ScrollDown, &self.scroll_down),
+		];
+		let format = commands.iter().fold(format, |format, (button, command)| match command {
+			Some(command) => bind(format, button, command),
+			None => format,
+		});
+		match &self.background {
+			Some(color) => background(format, color),
+			None => format,
+		}
+	}
+}
+
+/// Makes `button` run `command` on the whole of `format`, replacing the widget's own actions for
+/// that button
+pub fn bind(format: Format, button: &MouseButton, command: &str) -> Format {
+	let action = ClickAction::ShellCommand(button.clone(), command.to_owned());
+	Format::Clickable(action, Box::new(unbind(format, button)))
+}
+
+/// Removes the click actions for `button` from `format`
+fn unbind(format: Format, button: &MouseButton) -> Format {
+	match format {
+		Format::Clickable(ClickAction::Function(bound, _), inner)
+		| Format::Clickable(ClickAction::ShellCommand(bound, _), inner)
+			if bound == *button =>
+		{
+			unbind(*inner, button)
+		}
+		Format::Clickable(action, inner) => {
+			Format::Clickable(action, Box::new(unbind(*inner, button)))
+		}
+		Format::Concat(parts) => {
+			Format::Concat(parts.into_iter().map(|part| Box::new(unbind(*part, button))).collect())
+		}
+		Format::Align(align, inner) => Format::Align(align, Box::new(unbind(*inner, button))),
+		Format::FgColor(color, inner) => Format::FgColor(color, Box::new(unbind(*inner, button))),
+		Format::BgColor(color, inner) => Format::BgColor(color, Box::new(unbind(*inner, button))),
+		Format::NoSeparator(inner) => Format::NoSeparator(Box::new(unbind(*inner, button))),
+		Format::Padding(padding, inner) => {
+			Format::Padding(padding, Box::new(unbind(*inner, button)))
+		}
+		text => text,
+	}
+}
+
+/// The number of characters of text in `format`
+pub fn width(format: &Format) -> usize {
+	match format {
+		Format::Str(text) | Format::UnescapedStr(text) => text.chars().count(),
+		Format::Concat(parts) => parts.iter().map(|part| width(part)).sum(),
+		Format::Align(_, inner)
+		| Format::FgColor(_, inner)
+		| Format::BgColor(_, inner)
+		| Format::NoSeparator(inner)
+		| Format::Padding(_, inner)
+		| Format::Clickable(_, inner) => width(inner),
+	}
+}
+
+/// The first or last piece of text in `format`
+fn edge(format: &mut Format, last: bool) -> Option<&mut String> {
+	match format {
+		Format::Str(text) | Format::UnescapedStr(text) => Some(text),
+		Format::Concat(parts) if last => parts.iter_mut().rev().find_map(|part| edge(part, last)),
+		Format::Concat(parts) => parts.iter_mut().find_map(|part| edge(part, last)),
+		Format::Align(_, inner)
+		| Format::FgColor(_, inner)
+		| Format::BgColor(_, inner)
+		| Format::NoSeparator(inner)
+		| Format::Padding(_, inner)
+		| Format::Clickable(_, inner) => edge(inner, last),
+	}
+}
+
+/// Sets the background color of `format`, unless it has no text
+pub fn background(format: Format, color: &Color) -> Format {
+	if width(&format) == 0 {
+		return format;
+	}
+	Format::BgColor(color.to_string(), Box::new(format))
+}
+
+/// Pads `format` with spaces to be at least `min_width` characters wide. unixbar's formats have
+/// no way to set the i3bar `min_width` and `align` block properties, so the padding is added to
+/// the text itself, which works with every formatter.
+pub fn pad(mut format: Format, min_width: usize, align: Align) -> Format {
+	let missing = min_width.saturating_sub(width(&format));
+	// Hidden widgets stay hidden
+	if missing == 0 || width(&format) == 0 {
+		return format;
+	}
+	let (before, after) = match align {
+		Align::Left => (0, missing),
+		Align::Center => (missing / 2, missing - missing / 2),
+		Align::Right => (missing, 0),
+	};
+	if let Some(text) = edge(&mut format, false) {
+		text.insert_str(0, &" ".repeat(before));
+	}
+	if let Some(text) = edge(&mut format, true) {
+		text.push_str(&" ".repeat(after));
+	}
+	format
+}
@@ -0,0 +1,186 @@
+use std::{
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc,
+	},
+	time::Duration,
+};
+
+use anyhow::anyhow;
+use unixbar::{format::I3BarFormatter, UnixBar};
+
+#[cfg(feature = "music")]
+use crate::widgets::Music;
+#[cfg(any(feature = "alsa", feature = "pulse"))]
+use crate::widgets::Volume;
+#[cfg(feature = "pulse")]
+use crate::widgets::{Microphone, Output};
+use crate::{
+	block::Block,
+	config::{Config, Visibility},
+	events::Events,
+	hardware::Hardware,
+	layout::Layout,
+	process,
+	sampler::Sampler,
+	watchdog::Watchdog,
+	widgets::{
+		show_error, Bar, BarWidget, Battery, Brightness, Buttons, Clock, Compact, Context, Disk,
+		Flair, Keyboard, Load, Mail, Memory, Monitor, Network, NightLight, Power, Recording,
+		Sparkline, Stalled, Sun, Tasks, Temperature, Traffic, Updates, Window, Workspaces,
+	},
+};
+
+/// Constructs a widget
+pub type Constructor = fn(&Context) -> Box<dyn BarWidget>;
+
+/// Every widget, by the name used for it in the configuration. Widgets disabled at build time
+/// are left out.
+pub const WIDGETS: &[(&str, Constructor)] = &[
+	("recording", |cx| Box::new(Recording::new(cx))),
+	#[cfg(feature = "music")]
+	("music", |cx| Box::new(Music::new(cx))),
+	#[cfg(any(feature = "alsa", feature = "pulse"))]
+	("volume", |cx| Box::new(Volume::new(cx))),
+	#[cfg(feature = "pulse")]
+	("output", |cx| Box::new(Output::new(cx))),
+	#[cfg(feature = "pulse")]
+	("microphone", |cx| Box::new(Microphone::new(cx))),
+	("keyboard", |cx| Box::new(Keyboard::new(cx))),
+	("mail", |cx| Box::new(Mail::new(cx))),
+	("disk", |cx| Box::new(Disk::new(cx))),
+	("network", |cx| Box::new(Network::new(cx))),
+	("traffic", |cx| Box::new(Traffic::new(cx))),
+	("load", |cx| Box::new(Load::new(cx))),
+	("sparkline", |cx| Box::new(Sparkline::new(cx))),
+	("memory", |cx| Box::new(Memory::new(cx))),
+	("temperature", |cx| Box::new(Temperature::new(cx))),
+	("updates", |cx| Box::new(Updates::new(cx))),
+	("tasks", |cx| Box::new(Tasks::new(cx))),
+	("sun", |cx| Box::new(Sun::new(cx))),
+	("nightlight", |cx| Box::new(NightLight::new(cx))),
+	("battery", |cx| Box::new(Battery::new(cx))),
+	("brightness", |cx| Box::new(Brightness::new(cx))),
+	("monitor", |cx| Box::new(Monitor::new(cx))),
+	("workspaces", |cx| Box::new(Workspaces::new(cx))),
+	("window", |cx| Box::new(Window::new(cx))),
+	("clock", |cx| Box::new(Clock::new(cx))),
+	("flair", |cx| Box::new(Flair::new(cx))),
+	("buttons", |cx| Box::new(Buttons::new(cx))),
+	("power", |cx| Box::new(Power::new(cx))),
+];
+
+/// Assembles a bar out of widgets
+pub struct BarBuilder<'a> {
+	config: &'a Config,
+	events: Events,
+	compact: Compact,
+	layout: Arc<Layout>,
+	sampler: Sampler,
+	hardware: Hardware,
+	watchdog: Watchdog,
+	widgets: Vec<Box<dyn BarWidget>>,
+	/// The blocks of the configured widgets, whose errors are shown on click
+	blocks: Vec<Block>,
+}
+
+impl<'a> BarBuilder<'a> {
+	/// Creates a builder constructing widgets from `config`
+	pub fn new(config: &'a Config) -> anyhow::Result<Self> {
+		let events = Events::open(&config.events)?;
+		let compact = Arc::new(AtomicBool::new(config.compact));
+		let layout = Arc::new(Layout::new(config.max_width));
+		process::set_limit(config.max_processes);
+		Ok(BarBuilder {
+			config,
+			events,
+			compact,
+			layout,
+			sampler: Sampler::new(),
+			hardware: Hardware::detect(),
+			watchdog: Watchdog::new(&config.watchdog),
+			widgets: Vec::new(),
+			blocks: Vec::new(),
+		})
+	}
+
+	/// Adds the widget called `name`
+	pub fn widget(mut self, name: &str) -> anyhow::Result<Self> {
+		let (_, constructor) = WIDGETS
+			.iter()
+			.find(|(widget, _)| *widget == name)
+			.ok_or_else(|| anyhow!("Unknown widget {}", name))?;
+		let visibility = self.config.blocks.get(name).map(|block| block.visibility);
+		let visibility = visibility.unwrap_or_default();
+		if visibility == Visibility::Never {
+			return Ok(self);
+		}
+		// Spread the configured widgets' updates across a second
+		let index = self.widgets.len();
+		let count = self.config.widgets.len().max(index + 1);
+		let phase = Duration::from_millis((index * 1000 / count) as u64);
+		let heartbeat = self.watchdog.register(name);
+		let block = Block::new(
+			name,
+			self.config,
+			self.compact.clone(),
+			self.layout.clone(),
+			phase,
+			heartbeat,
+		);
+		let cx = Context {
+			config: self.config,
+			events: &self.events,
+			block: &block,
+			sampler: &self.sampler,
+			hardware: self.hardware,
+		};
+		let widget = constructor(&cx);
+		if visibility == Visibility::Auto && !widget.visible() {
+			eprintln!(
+				"Hiding the {} widget, it has nothing to show. Set blocks.{}.visibility to \"always\" \
+				 to show it anyway.",
+				name, name
+			);
+			return Ok(self);
+		}
+		self.widgets.push(widget);
+		self.blocks.push(block);
+		Ok(self)
+	}
+
+	/// Adds all widgets listed in the configuration, in order
+	pub fn configured(self) -> anyhow::Result<Self> {
+		let config = self.config;
+		config.widgets.iter().try_fold(self, |builder, name| builder.widget(name))
+	}
+
+	/// Adds an already constructed widget
+	pub fn add(mut self, widget: Box<dyn BarWidget>) -> Self {
+		self.widgets.push(widget);
+		self
+	}
+
+	/// Creates the bar with all added widgets
+	pub fn build(self) -> Bar {
+		let mut bar = UnixBar::new(I3BarFormatter::new());
+		let compact = self.compact;
+		bar.register_fn("compact_toggle", move || {
+			compact.fetch_xor(true, Ordering::Relaxed);
+		});
+		// Widgets render once when they're added, so the first snapshot has to be taken first
+		self.sampler.start();
+		if self.watchdog.enabled() {
+			Box::new(Stalled::new(&self.watchdog, &self.config.watchdog, self.config.icons))
+				.attach(&mut bar);
+			self.watchdog.start();
+		}
+		for block in self.blocks {
+			bar.register_fn(&block.error_fn(), move || show_error(&block));
+		}
+		for widget in self.widgets {
+			widget.attach(&mut bar);
+		}
+		bar
+	}
+}
@@ -0,0 +1,55 @@
+use std::{
+	sync::{Arc, Mutex},
+	thread,
+	time::Duration,
+};
+
+use crate::watchdog::Heartbeat;
+
+/// The result of a slow operation, such as a command checking for updates. The operation is run
+/// on an interval in the background, so widgets reading the result never hold up the bar.
+pub struct Cached<T> {
+	value: Arc<Mutex<Option<T>>>,
+}
+
+impl<T: Clone + Send + 'static> Cached<T> {
+	/// Runs `refresh` now and then every `interval` on a background thread. The thread is
+	/// supervised through the widget's `heartbeat`, and replaced if it dies or hangs.
+	pub fn spawn<F>(interval: Duration, heartbeat: &Heartbeat, refresh: F) -> Self
+	where
+		F: Fn() -> T + Send + Sync + 'static,
+	{
+		let value = Arc::new(Mutex::new(None));
+		let shared = value.clone();
+		let refresh = Arc::new(refresh);
+		heartbeat.supervise(interval, move |pulse| {
+			let (shared, refresh) = (shared.clone(), refresh.clone());
+			thread::spawn(move || loop {
+				let result = refresh();
+				// A replaced worker may have hung for a while, its result is stale
+				if !pulse.beat() {
+					break;
+				}
+				*shared.lock().unwrap() = Some(result);
+				thread::sleep(interval);
+			});
+		});
+		Cached { value }
+	}
+
+	/// The latest result, or `None` until the operation has finished once
+	pub fn get(&self) -> Option<T> {
+		self.value.lock().unwrap().clone()
+	}
+
+	/// Replaces the result until the next refresh, e.g. after the widget changed what it reads
+	pub fn set(&self, value: T) {
+		*self.value.lock().unwrap() = Some(value);
+	}
+}
+
+impl<T> Clone for Cached<T> {
+	fn clone(&self) -> Self {
+		Cached { value: self.value.clone() }
+	}
+}
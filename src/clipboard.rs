@@ -0,0 +1,48 @@
+use std::{
+	env,
+	io::{ErrorKind as IoErrorKind, Write},
+	process::{Command, Stdio},
+};
+
+use anyhow::anyhow;
+
+use crate::{config::ClipboardTool, notify::Notification};
+
+impl ClipboardTool {
+	/// Picks wl-copy on Wayland, and xclip otherwise
+	pub fn detect() -> Self {
+		match env::var_os("WAYLAND_DISPLAY") {
+			Some(_) => ClipboardTool::WlCopy,
+			None => ClipboardTool::Xclip,
+		}
+	}
+
+	fn command(self) -> Command {
+		let (program, args): (&str, &[&str]) = match self {
+			ClipboardTool::WlCopy => ("wl-copy", &[]),
+			ClipboardTool::Xclip => ("xclip", &["-selection", "clipboard"]),
+			ClipboardTool::Xsel => ("xsel", &["--clipboard", "--input"]),
+		};
+		let mut command = Command::new(program);
+		command.args(args);
+		command
+	}
+}
+
+/// Puts `text` on the clipboard with `tool`, or a detected tool when `None`, and confirms it
+/// with a notification
+pub fn copy(tool: Option<ClipboardTool>, text: &str) -> anyhow::Result<()> {
+	let tool = tool.unwrap_or_else(ClipboardTool::detect);
+	let mut child = match tool.command().stdin(Stdio::piped()).spawn() {
+		Ok(child) => child,
+		Err(e) if e.kind() == IoErrorKind::NotFound => {
+			return Err(anyhow!("No clipboard tool found, install wl-copy or xclip"))
+		}
+		Err(e) => return Err(e.into()),
+	};
+	child.stdin.take().unwrap().write_all(text.as_bytes())?;
+	if !child.wait()?.success() {
+		return Err(anyhow!("Couldn't copy to the clipboard"));
+	}
+	Notification::new("Copied to clipboard", Some(text), Some("edit-copy")).show()
+}
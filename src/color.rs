@@ -0,0 +1,123 @@
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+/// Colors that can be referred to by name
+const NAMED: &[(&str, &str)] = &[
+	("black", "#000000"),
+	("white", "#ffffff"),
+	("gray", "#808080"),
+	("red", "#ff0000"),
+	("green", "#00ff00"),
+	("blue", "#0000ff"),
+	("yellow", "#ffff00"),
+	("cyan", "#00ffff"),
+	("magenta", "#ff00ff"),
+];
+
+/// A color in the `#RRGGBB` or `#RRGGBBAA` format understood by i3bar. Named colors and the
+/// short `#RGB` form are converted to this format when parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String")]
+pub struct Color(String);
+
+impl Color {
+	/// The color with the given red, green and blue components
+	pub fn rgb(r: u8, g: u8, b: u8) -> Self {
+		Color(format!("#{:02x}{:02x}{:02x}", r, g, b))
+	}
+
+	/// The red, green and blue components of the color
+	pub fn components(&self) -> (u8, u8, u8) {
+		let channel = |i: usize| u8::from_str_radix(&self.0[i..i + 2], 16).unwrap_or(0);
+		(channel(1), channel(3), channel(5))
+	}
+
+	/// The color `fraction` of the way from `self` to `other`
+	pub fn mix(&self, other: &Color, fraction: f64) -> Color {
+		let fraction = fraction.max(0.0).min(1.0);
+		let ((r1, g1, b1), (r2, g2, b2)) = (self.components(), other.components());
+		let mix =
+			|a: u8, b: u8| (f64::from(a) + (f64::from(b) - f64::from(a)) * fraction).round() as u8;
+		Color::rgb(mix(r1, r2), mix(g1, g2), mix(b1, b2))
+	}
+
+	/// The color as a hex string
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+}
+
+impl FromStr for Color {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if let Some((_, hex)) = NAMED.iter().find(|(name, _)| name.eq_ignore_ascii_case(s)) {
+			return Ok(Color(String::from(*hex)));
+		}
+		let digits = s
+			.strip_prefix('#')
+			.ok_or_else(|| anyhow!("invalid color {:?}, expected #RRGGBB or a color name", s))?;
+		if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+			return Err(anyhow!("invalid color {:?}, expected hex digits after #", s));
+		}
+		match digits.len() {
+			// Each digit of the short form stands for two
+			3 => Ok(Color(digits.chars().fold(String::from("#"), |mut hex, c| {
+				hex.push(c);
+				hex.push(c);
+				hex
+			}))),
+			6 | 8 => Ok(Color(s.to_owned())),
+			_ => Err(anyhow!("invalid color {:?}, expected #RGB, #RRGGBB or #RRGGBBAA", s)),
+		}
+	}
+}
+
+impl TryFrom<String> for Color {
+	type Error = anyhow::Error;
+
+	fn try_from(s: String) -> Result<Self, Self::Error> {
+		s.parse()
+	}
+}
+
+impl fmt::Display for Color {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(&self.0)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn valid() {
+		assert_eq!("#9090ff".parse::<Color>().unwrap().as_str(), "#9090ff");
+		assert_eq!("#9090FF80".parse::<Color>().unwrap().as_str(), "#9090FF80");
+		assert_eq!("#f0a".parse::<Color>().unwrap().as_str(), "#ff00aa");
+		assert_eq!("Red".parse::<Color>().unwrap().as_str(), "#ff0000");
+	}
+
+	#[test]
+	fn missing_hash() {
+		assert!("9090ff".parse::<Color>().is_err());
+		assert!("".parse::<Color>().is_err());
+	}
+
+	#[test]
+	fn bad_hex() {
+		assert!("#9090fg".parse::<Color>().is_err());
+		assert!("#xyz".parse::<Color>().is_err());
+		assert!("#+90".parse::<Color>().is_err());
+	}
+
+	#[test]
+	fn wrong_length() {
+		for color in &["#", "#90", "#9090", "#9090f", "#9090fff", "#9090ff8", "#9090ff800"] {
+			assert!(color.parse::<Color>().is_err(), "{} was accepted", color);
+		}
+	}
+}
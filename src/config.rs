@@ -0,0 +1,1418 @@
+use std::{
+	collections::HashMap, convert::TryFrom, env, fs, io::ErrorKind as IoErrorKind, path::PathBuf,
+};
+
+use anyhow::anyhow;
+use chrono::format::{Item, StrftimeItems};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize, Serializer};
+use toml::{value::Table, Value};
+
+use crate::{builder::WIDGETS, color::Color, dirs};
+
+/// User configuration, read from `$XDG_CONFIG_HOME/agrabar/config.toml`. Every field is
+/// optional, and missing fields keep their default value.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+	/// Names of the widgets to show, from left to right
+	pub widgets: Vec<String>,
+	/// Block settings for each widget, by widget name
+	pub blocks: HashMap<String, BlockConfig>,
+	/// Whether to start with widgets collapsed to their icons. Toggled by clicking the flair.
+	pub compact: bool,
+	/// The maximum width of the bar in characters. When exceeded, widgets with a compact variant
+	/// are collapsed, starting with the lowest `priority`.
+	pub max_width: Option<usize>,
+	pub recording: RecordingConfig,
+	pub music: MusicConfig,
+	pub battery: BatteryConfig,
+	pub network: NetworkConfig,
+	pub traffic: TrafficConfig,
+	pub volume: VolumeConfig,
+	pub output: OutputConfig,
+	pub microphone: MicrophoneConfig,
+	pub device_menu: DeviceMenuConfig,
+	pub brightness: BrightnessConfig,
+	pub monitor: MonitorConfig,
+	pub clock: ClockConfig,
+	pub keyboard: KeyboardConfig,
+	pub mail: MailConfig,
+	pub disk: DiskConfig,
+	pub load: LoadConfig,
+	pub sparkline: SparklineConfig,
+	pub memory: MemoryConfig,
+	pub temperature: TemperatureConfig,
+	pub updates: UpdatesConfig,
+	pub sun: SunConfig,
+	pub nightlight: NightLightConfig,
+	pub tasks: TasksConfig,
+	pub workspaces: WorkspacesConfig,
+	pub window: WindowConfig,
+	pub flair: FlairConfig,
+	pub buttons: ButtonsConfig,
+	pub power: PowerConfig,
+	pub gauge: GaugeConfig,
+	/// The program used to copy widget contents to the clipboard. Detected when unset.
+	pub clipboard: Option<ClipboardTool>,
+	/// How long in seconds a widget may fail to update before its error is shown, e.g. while
+	/// sensors come back after resuming from suspend. `unavailable_placeholder` is shown until
+	/// then.
+	pub unavailable_secs: u64,
+	/// Shown in place of a widget whose updates have failed for less than `unavailable_secs`
+	pub unavailable_placeholder: String,
+	/// How many commands widgets may run at once to read their data, e.g. nmcli or playerctl.
+	/// Widgets updating at the same moment wait for each other beyond that.
+	pub max_processes: usize,
+	/// How icons are drawn. `emoji` and `text` work with standard fonts, showing emoji or short
+	/// labels like `VOL` and `BAT` in place of Nerd Font glyphs.
+	pub icons: IconStyle,
+	pub events: EventsConfig,
+	pub osd: OsdConfig,
+	pub watchdog: WatchdogConfig,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Config {
+			widgets: [
+				"recording",
+				"music",
+				"volume",
+				"keyboard",
+				"disk",
+				"network",
+				"load",
+				"memory",
+				"temperature",
+				"battery",
+				"brightness",
+				"monitor",
+				"clock",
+				"flair",
+				"buttons",
+			]
+			.iter()
+			// Leave out widgets disabled at build time
+			.filter(|name| WIDGETS.iter().any(|(widget, _)| widget == *name))
+			.map(|name| String::from(*name))
+			.collect(),
+			blocks: HashMap::new(),
+			compact: false,
+			max_width: None,
+			recording: RecordingConfig::default(),
+			music: MusicConfig::default(),
+			battery: BatteryConfig::default(),
+			network: NetworkConfig::default(),
+			traffic: TrafficConfig::default(),
+			volume: VolumeConfig::default(),
+			output: OutputConfig::default(),
+			microphone: MicrophoneConfig::default(),
+			device_menu: DeviceMenuConfig::default(),
+			brightness: BrightnessConfig::default(),
+			monitor: MonitorConfig::default(),
+			clock: ClockConfig::default(),
+			keyboard: KeyboardConfig::default(),
+			mail: MailConfig::default(),
+			disk: DiskConfig::default(),
+			load: LoadConfig::default(),
+			sparkline: SparklineConfig::default(),
+			memory: MemoryConfig::default(),
+			temperature: TemperatureConfig::default(),
+			updates: UpdatesConfig::default(),
+			sun: SunConfig::default(),
+			nightlight: NightLightConfig::default(),
+			tasks: TasksConfig::default(),
+			workspaces: WorkspacesConfig::default(),
+			window: WindowConfig::default(),
+			flair: FlairConfig::default(),
+			buttons: ButtonsConfig::default(),
+			power: PowerConfig::default(),
+			gauge: GaugeConfig::default(),
+			clipboard: None,
+			unavailable_secs: 5,
+			unavailable_placeholder: String::from("—"),
+			max_processes: 4,
+			icons: IconStyle::Glyphs,
+			events: EventsConfig::default(),
+			osd: OsdConfig::default(),
+			watchdog: WatchdogConfig::default(),
+		}
+	}
+}
+
+/// Display settings shared by all widgets
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BlockConfig {
+	/// The minimum width of the widget in characters, to keep changing numbers from moving the
+	/// rest of the bar
+	pub min_width: Option<usize>,
+	/// How to align the text when it's narrower than `min_width`
+	pub align: Align,
+	/// The background color of the widget. The bar's background shows through when unset.
+	pub background: Option<Color>,
+	/// Widgets with a lower priority are collapsed first when the bar exceeds `max_width`
+	pub priority: i32,
+	/// A shell command run when scrolling up on the widget, in place of its own scroll action
+	pub scroll_up: Option<String>,
+	/// A shell command run when scrolling down on the widget, in place of its own scroll action
+	pub scroll_down: Option<String>,
+	/// How long the widget waits before its first update, in milliseconds, so widgets updating
+	/// on the same schedule don't all poll at once. Widgets are spread evenly across a second
+	/// by default.
+	pub phase_ms: Option<u64>,
+	/// Whether to show the widget. By default, widgets with nothing to show on this machine are
+	/// left out, like the battery widget on a desktop.
+	pub visibility: Visibility,
+}
+
+/// Whether a widget is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+	/// Shown if it has anything to show
+	#[default]
+	Auto,
+	Always,
+	Never,
+}
+
+/// Text alignment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Align {
+	#[default]
+	Left,
+	Center,
+	Right,
+}
+
+/// Configuration for the screen recording indicator
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RecordingConfig {
+	/// A file that exists while a recording is in progress. The indicator is hidden when unset.
+	pub lock_file: Option<PathBuf>,
+	pub color: Color,
+}
+
+impl Default for RecordingConfig {
+	fn default() -> Self {
+		RecordingConfig { lock_file: None, color: color("#ff5555") }
+	}
+}
+
+/// Configuration for the music widget
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MusicConfig {
+	/// How media players are read and controlled
+	pub backend: MusicBackend,
+	/// Show the song using `short_format`, for narrow screens
+	pub short: bool,
+	/// The layout of the song. The placeholders `{icon}`, `{artist}`, `{album}`, `{title}`,
+	/// `{track}` and `{rate}` are replaced with the playback icon and song metadata, or left
+	/// empty if the player doesn't provide them.
+	pub format: String,
+	/// The layout of the song when `short` is set
+	pub short_format: String,
+	/// Whether left clicking the playback icon copies the song, laid out by `copy_format`,
+	/// instead of going to the previous song
+	pub copy: bool,
+	/// The text copied to the clipboard when clicking the playback icon, with the same
+	/// placeholders as `format` except `{icon}`
+	pub copy_format: String,
+	/// The color of the widget
+	pub color: Color,
+	/// Whether to color the widget with the dominant color of the album art instead, when the
+	/// player provides art. Needs the `album-art` feature.
+	pub album_accent: bool,
+}
+
+impl Default for MusicConfig {
+	fn default() -> Self {
+		MusicConfig {
+			backend: MusicBackend::Mpris,
+			short: false,
+			format: String::from("{icon}  {artist} - {title}"),
+			short_format: String::from("{icon} {title}"),
+			copy: false,
+			copy_format: String::from("Now playing: {artist} - {title}"),
+			color: color("#9090ff"),
+			album_accent: false,
+		}
+	}
+}
+
+/// A way of talking to media players
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MusicBackend {
+	/// MPRIS over D-Bus, in process
+	Mpris,
+	/// The `playerctl` command, for players that misbehave with the in-process client
+	Playerctl,
+}
+
+/// Configuration for the battery widget
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BatteryConfig {
+	/// Capacity in percent at or below which the critical notification is sent
+	pub threshold: u8,
+	/// Icon name of the critical notification
+	pub icon: String,
+	/// Urgency of the critical notification
+	pub urgency: Urgency,
+	/// Whether to attach the capacity as a `value` hint, which some notification daemons draw as
+	/// a gauge
+	pub value_hint: bool,
+	/// Capacity icons from empty to full, each covering an equal share of the range. Taken from
+	/// the icon style when unset.
+	pub icons: Option<Vec<String>>,
+	/// Colors from empty to full, each covering an equal share of the range
+	pub colors: Vec<Color>,
+	/// Icon shown in front of the capacity icon while charging. Taken from the icon style when
+	/// unset.
+	pub charging_icon: Option<String>,
+	/// Icon shown instead of the charging icon when charging has reached the limit set in
+	/// `charge_control_end_threshold`. Taken from the icon style when unset.
+	pub limited_icon: Option<String>,
+	/// Capacity in percent at which to notify that charging is complete, e.g. 100, or 80 to
+	/// preserve battery health. Disabled when unset.
+	pub full_level: Option<u8>,
+	/// Where to read the battery state from
+	pub backend: BatteryBackend,
+	/// Whether to show the estimated time until the battery is empty or full
+	pub show_time: bool,
+	/// The layout of the widget, with the placeholders `{charging}`, `{icon}`, `{value}` and
+	/// `{time}`
+	pub format: String,
+	/// Whether to flash the background while the capacity is critical
+	pub flash: bool,
+	/// The background color alternated with the usual one while flashing
+	pub flash_color: Color,
+}
+
+impl Default for BatteryConfig {
+	fn default() -> Self {
+		BatteryConfig {
+			threshold: 10,
+			icon: String::from("battery-caution"),
+			urgency: Urgency::Critical,
+			value_hint: false,
+			icons: None,
+			colors: ["#FF4000", "#FFAE00", "#FFF600", "#A8FF00", "#50FF00"]
+				.iter()
+				.copied()
+				.map(color)
+				.collect(),
+			charging_icon: None,
+			limited_icon: None,
+			full_level: None,
+			backend: BatteryBackend::Systemstat,
+			show_time: false,
+			format: String::from("{charging}{icon} {value}%{time}"),
+			flash: false,
+			flash_color: color("#FF0000"),
+		}
+	}
+}
+
+/// A source of battery readings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatteryBackend {
+	Systemstat,
+	/// UPower over D-Bus. Its warning level is used instead of `threshold` when it reports one.
+	/// Falls back to systemstat if UPower isn't running.
+	Upower,
+}
+
+/// Configuration for the volume widget
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct VolumeConfig {
+	/// The sound system the volume is read from and changed through
+	pub backend: VolumeBackend,
+	/// Whether to start with scrolling locked. Toggled by right clicking the widget.
+	pub locked: bool,
+	/// The number of decimals of the volume percentage
+	pub precision: usize,
+	/// Whether to show the volume in decibels using `db_format`, rather than in percent
+	pub decibels: bool,
+	/// Whether to show the volume as text, as a gauge, or both
+	pub display: LevelDisplay,
+	/// The layout of the widget, with the placeholders `{icon}` and `{value}`
+	pub format: String,
+	/// The layout of the widget while muted, with the placeholder `{icon}`
+	pub muted_format: String,
+	/// Whether to keep showing the volume while muted, struck through, instead of
+	/// `muted_format`
+	pub strike_muted: bool,
+	/// The layout of the widget when showing decibels
+	pub db_format: String,
+}
+
+impl Default for VolumeConfig {
+	fn default() -> Self {
+		VolumeConfig {
+			backend: VolumeBackend::Alsa,
+			locked: false,
+			precision: 0,
+			decibels: false,
+			display: LevelDisplay::Percent,
+			format: String::from("{icon} {value}%"),
+			muted_format: String::from("{icon} MUTE"),
+			strike_muted: false,
+			db_format: String::from("{icon} {value} dB"),
+		}
+	}
+}
+
+/// How a level such as the volume is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LevelDisplay {
+	/// As text, using the widget's format
+	Percent,
+	/// As a gauge next to the icon
+	Gauge,
+	/// As text followed by a gauge
+	Both,
+}
+
+/// A sound system controlling the volume
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VolumeBackend {
+	/// The ALSA `Master` control
+	Alsa,
+	/// The default PulseAudio sink. On systems running PulseAudio on top of ALSA, this is the
+	/// volume applications are actually played back at, while `Master` may differ from it.
+	Pulse,
+}
+
+/// Configuration for the audio output device widget
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct OutputConfig {
+	/// The maximum length of the device name in characters. Longer names are cut short.
+	pub max_length: usize,
+}
+
+impl Default for OutputConfig {
+	fn default() -> Self {
+		OutputConfig { max_length: 20 }
+	}
+}
+
+/// Configuration for the audio input device widget
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MicrophoneConfig {
+	/// The maximum length of the device name in characters. Longer names are cut short.
+	pub max_length: usize,
+}
+
+impl Default for MicrophoneConfig {
+	fn default() -> Self {
+		MicrophoneConfig { max_length: 20 }
+	}
+}
+
+/// Which devices the audio device and microphone pickers offer
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DeviceMenuConfig {
+	/// Regular expressions matched against the name and description of each device. Matching
+	/// devices are left out, e.g. `["^Dummy", "HDMI"]`.
+	pub hidden: Vec<String>,
+	/// Whether to leave out the monitor sources, which record what a sink plays
+	pub hide_monitors: bool,
+	/// Friendly labels for devices and sound cards, by their PulseAudio name, e.g.
+	/// `"alsa_output.pci-0000_00_1f.3.analog-stereo" = "Desk Speakers"`. Devices without one are
+	/// shown by their description. The names are listed by `pactl list short sinks` and
+	/// `pactl list short sources`.
+	pub names: HashMap<String, String>,
+}
+
+impl Default for DeviceMenuConfig {
+	fn default() -> Self {
+		DeviceMenuConfig { hidden: Vec::new(), hide_monitors: true, names: HashMap::new() }
+	}
+}
+
+/// Configuration for the brightness widget
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BrightnessConfig {
+	/// The layout of the widget, with the placeholders `{icon}` and `{value}`
+	pub format: String,
+	/// Whether to show the brightness in percent, as a gauge, or both
+	pub display: LevelDisplay,
+	/// Whether to start with scrolling locked. Toggled by right clicking the widget.
+	pub locked: bool,
+}
+
+impl Default for BrightnessConfig {
+	fn default() -> Self {
+		BrightnessConfig {
+			format: String::from("{icon} {value}%"),
+			display: LevelDisplay::Percent,
+			locked: false,
+		}
+	}
+}
+
+/// Configuration for the monitor widget, controlling the brightness of external monitors over
+/// DDC/CI with ddcutil
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MonitorConfig {
+	/// The layout of the widget, with the placeholders `{icon}`, `{value}` and `{display}`, the
+	/// model of the monitor
+	pub format: String,
+	/// The ddcutil number of the display controlled at start, the first one found when unset.
+	/// Left clicking the widget switches to the next display.
+	pub display: Option<u32>,
+	/// How much a scroll step changes the brightness, in percent
+	pub step: u32,
+	/// How often the brightness is read, in seconds. Reading it over DDC/CI is slow.
+	pub interval_secs: u64,
+	pub color: Color,
+}
+
+impl Default for MonitorConfig {
+	fn default() -> Self {
+		MonitorConfig {
+			format: String::from("{icon} {value}%"),
+			display: None,
+			step: 5,
+			interval_secs: 10,
+			color: color("#ffff55"),
+		}
+	}
+}
+
+/// Configuration for the disk widget
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DiskConfig {
+	/// Free space in GB at or below which to send a notification. Disabled when unset.
+	pub alert: Option<f64>,
+	/// The layout of the widget, with the placeholders `{icon}`, `{value}` and `{unit}`
+	pub format: String,
+	/// The number of decimals the free space is shown with
+	pub precision: usize,
+	/// The unit the free space is shown in. A fixed unit like `gb` or `gib` keeps the widget
+	/// from changing width when the free space crosses a unit boundary.
+	pub unit: ByteUnit,
+}
+
+impl Default for DiskConfig {
+	fn default() -> Self {
+		DiskConfig {
+			alert: None,
+			format: String::from("{icon} {value} {unit}"),
+			precision: 1,
+			unit: ByteUnit::Auto,
+		}
+	}
+}
+
+/// A unit byte counts are shown in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ByteUnit {
+	/// The largest decimal unit the value is at least 1 of, e.g. `GB`, as systemstat shows sizes
+	Auto,
+	/// The largest binary unit the value is at least 1 of, e.g. `GiB`
+	Binary,
+	B,
+	KB,
+	MB,
+	GB,
+	TB,
+	KiB,
+	MiB,
+	GiB,
+	TiB,
+}
+
+/// Configuration for the load widget
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LoadConfig {
+	/// The layout of the widget, with the placeholders `{icon}` and `{value}`
+	pub format: String,
+	/// Whether to show the 1, 5 and 15 minute averages like `uptime`, rather than only the 1
+	/// minute average
+	pub all: bool,
+	/// Whether to color the widget by the 1 minute average divided by the number of CPU cores,
+	/// using the thresholds below. A load of 4 is fine on 8 cores, but not on 2.
+	pub per_core_color: bool,
+	/// The color of the widget
+	pub color: Color,
+	/// The load per core above which `warning_color` is used
+	pub warning: f32,
+	pub warning_color: Color,
+	/// The load per core above which `critical_color` is used
+	pub critical: f32,
+	pub critical_color: Color,
+	/// The number of readings averaged into the shown load, to keep it from flickering. 1 shows
+	/// every reading as is.
+	pub smoothing: usize,
+}
+
+impl Default for LoadConfig {
+	fn default() -> Self {
+		LoadConfig {
+			format: String::from("{icon} {value}"),
+			all: false,
+			per_core_color: false,
+			color: color("#cc9999"),
+			warning: 0.7,
+			warning_color: color("#ffc300"),
+			critical: 1.0,
+			critical_color: color("#ff5555"),
+			smoothing: 1,
+		}
+	}
+}
+
+/// Configuration for the sparkline widget
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SparklineConfig {
+	/// What to show the history of
+	pub source: SparklineSource,
+	/// The number of samples shown, one per second
+	pub width: usize,
+	/// The interface to show the throughput of. Detected from the active connection when unset.
+	pub interface: Option<String>,
+	/// The number of readings averaged into each sample. 1 shows every reading as is.
+	pub smoothing: usize,
+}
+
+impl Default for SparklineConfig {
+	fn default() -> Self {
+		SparklineConfig { source: SparklineSource::Cpu, width: 10, interface: None, smoothing: 1 }
+	}
+}
+
+/// A metric shown as a sparkline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SparklineSource {
+	/// The share of time the CPU is busy
+	Cpu,
+	/// The share of memory in use
+	Memory,
+	/// The combined download and upload rate
+	Network,
+}
+
+/// Configuration for the memory widget
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MemoryConfig {
+	/// Free memory in GB at or below which to send a notification. Disabled when unset.
+	pub alert: Option<f64>,
+	/// The layout of the widget, with the placeholders `{icon}`, `{value}` and `{unit}`
+	pub format: String,
+}
+
+impl Default for MemoryConfig {
+	fn default() -> Self {
+		MemoryConfig { alert: None, format: String::from("{icon} {value} {unit}") }
+	}
+}
+
+/// Configuration for the temperature widget
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TemperatureConfig {
+	/// Temperature in °C at or above which to send a notification. Disabled when unset.
+	pub alert: Option<f64>,
+	/// The layout of the widget, with the placeholders `{icon}`, `{value}` and `{unit}`
+	pub format: String,
+	/// The unit the temperature is shown in. Thresholds are always given in °C.
+	pub unit: TemperatureUnit,
+	/// Whether clicking the widget switches to the other unit until the bar is restarted
+	pub toggle_unit: bool,
+	/// Which CPU temperature sensors to read
+	pub sensors: TemperatureSensors,
+	/// The number of readings averaged into the shown temperature, to keep it from flickering.
+	/// 1 shows every reading as is. Alerts use the latest reading.
+	pub smoothing: usize,
+}
+
+impl Default for TemperatureConfig {
+	fn default() -> Self {
+		TemperatureConfig {
+			alert: None,
+			format: String::from("{icon} {value} {unit}"),
+			unit: TemperatureUnit::Celsius,
+			toggle_unit: true,
+			sensors: TemperatureSensors::Single,
+			smoothing: 1,
+		}
+	}
+}
+
+/// The CPU temperature sensors shown by the temperature widget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemperatureSensors {
+	/// The single temperature reported by systemstat
+	Single,
+	/// The hottest CPU package, read from the coretemp sensors
+	Max,
+	/// Every CPU package, read from the coretemp sensors, e.g. `P0 55.0 P1 52.0`
+	All,
+}
+
+/// A unit of temperature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemperatureUnit {
+	Celsius,
+	Fahrenheit,
+}
+
+/// A program putting text on the clipboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardTool {
+	#[serde(rename = "wl-copy")]
+	WlCopy,
+	Xclip,
+	Xsel,
+}
+
+/// Configuration for the event stream read by external tools
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EventsConfig {
+	/// Where to write events as lines of JSON, e.g. a FIFO, or `/dev/fd/3` for a file descriptor.
+	/// Disabled when unset.
+	pub path: Option<PathBuf>,
+}
+
+/// Configuration for the package updates widget
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UpdatesConfig {
+	/// A shell command listing the available updates, one per line. On Debian, this could be
+	/// `apt list --upgradable 2>/dev/null | tail -n +2`.
+	pub command: String,
+	/// How often to check for updates, in seconds
+	pub interval_secs: u64,
+	/// Whether to hide the widget when there are no updates
+	pub hide_empty: bool,
+	/// The layout of the widget, with the placeholders `{icon}` and `{value}`
+	pub format: String,
+	/// A shell command run when clicking the widget, e.g. opening a terminal to update
+	pub on_click: Option<String>,
+}
+
+impl Default for UpdatesConfig {
+	fn default() -> Self {
+		UpdatesConfig {
+			command: String::from("checkupdates"),
+			interval_secs: 3600,
+			hide_empty: true,
+			format: String::from("{icon} {value}"),
+			on_click: None,
+		}
+	}
+}
+
+/// Configuration for the pending tasks widget
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TasksConfig {
+	/// Where the tasks are read from
+	pub source: TaskSource,
+	/// The todo.txt file, for the `file` source
+	pub file: Option<PathBuf>,
+	/// How often to count the tasks, in seconds
+	pub interval_secs: u64,
+	/// The layout of the widget, with the placeholders `{icon}` and `{value}`
+	pub format: String,
+	/// A shell command run when clicking the widget, e.g. opening a task manager
+	pub on_click: Option<String>,
+}
+
+impl Default for TasksConfig {
+	fn default() -> Self {
+		TasksConfig {
+			source: TaskSource::Taskwarrior,
+			file: None,
+			interval_secs: 60,
+			format: String::from("{icon} {value}"),
+			on_click: None,
+		}
+	}
+}
+
+/// Where pending tasks are read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskSource {
+	/// Taskwarrior's `task` command
+	Taskwarrior,
+	/// A todo.txt file, where done tasks start with `x `
+	File,
+}
+
+/// Configuration for the sunrise and sunset widget. The widget is hidden until both coordinates
+/// are set.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SunConfig {
+	/// Latitude in degrees, north positive
+	pub latitude: Option<f64>,
+	/// Longitude in degrees, east positive
+	pub longitude: Option<f64>,
+	/// Whether to show the moon phase as well
+	pub moon: bool,
+}
+
+/// Configuration for the night light widget, warming the screen's colors with redshift or
+/// gammastep. The widget is hidden when the tool isn't installed.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NightLightConfig {
+	/// The program adjusting the colors. Detected when unset, preferring gammastep.
+	pub tool: Option<NightLightTool>,
+	/// Whether to turn the night light on at start. Toggled by clicking the widget.
+	pub enabled: bool,
+	/// The color temperature in Kelvin while on. Changed by scrolling on the widget.
+	pub temperature: u32,
+	/// How much a scroll step changes the temperature, in Kelvin
+	pub step: u32,
+	/// The layout of the widget, with the placeholders `{icon}` and `{value}`, the temperature
+	pub format: String,
+}
+
+impl Default for NightLightConfig {
+	fn default() -> Self {
+		NightLightConfig {
+			tool: None,
+			enabled: false,
+			temperature: 4500,
+			step: 250,
+			format: String::from("{icon} {value}K"),
+		}
+	}
+}
+
+/// A program adjusting the color temperature of the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NightLightTool {
+	/// For X11
+	Redshift,
+	/// A fork of redshift that also works on Wayland
+	Gammastep,
+}
+
+/// Configuration for the i3 and sway workspace widget
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WorkspacesConfig {
+	/// The color of the workspace with the keyboard focus
+	pub focused_color: Color,
+	/// The color of workspaces shown on other outputs
+	pub visible_color: Color,
+	/// The color of workspaces with windows wanting attention
+	pub urgent_color: Color,
+	/// The color of the other workspaces
+	pub color: Color,
+}
+
+impl Default for WorkspacesConfig {
+	fn default() -> Self {
+		WorkspacesConfig {
+			focused_color: color("#ffffff"),
+			visible_color: color("#aaaaaa"),
+			urgent_color: color("#ff5555"),
+			color: color("#777777"),
+		}
+	}
+}
+
+/// Configuration for the clock
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ClockConfig {
+	/// The strftime formats cycled through by scrolling on the clock. The first one is shown at
+	/// startup. `{icon}` is replaced with the clock icon.
+	pub formats: Vec<String>,
+	/// A shell command run when clicking the clock, e.g. opening a calendar
+	pub on_click: Option<String>,
+	/// Show hours from 1 to 12 with AM or PM, rather than from 0 to 23
+	pub twelve_hour: bool,
+	/// Append the ISO week number to every format
+	pub week_number: bool,
+	/// The IANA timezone shown, e.g. `America/New_York`. Local time is shown when unset.
+	pub timezone: Option<Timezone>,
+	/// The locale used for month and day names, e.g. `de_DE`. Taken from the environment when
+	/// unset.
+	pub locale: Option<String>,
+}
+
+impl Default for ClockConfig {
+	fn default() -> Self {
+		ClockConfig {
+			formats: vec![String::from("{icon} %d/%m %H:%M")],
+			on_click: None,
+			twelve_hour: false,
+			week_number: false,
+			timezone: None,
+			locale: None,
+		}
+	}
+}
+
+/// An IANA timezone, checked when the configuration is loaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Timezone(pub Tz);
+
+impl Serialize for Timezone {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(self.0.name())
+	}
+}
+
+impl TryFrom<String> for Timezone {
+	type Error = anyhow::Error;
+
+	fn try_from(name: String) -> Result<Self, Self::Error> {
+		name.parse().map(Timezone).map_err(|_| {
+			anyhow!("unknown timezone {:?}, expected an IANA name like \"Europe/Berlin\"", name)
+		})
+	}
+}
+
+/// Configuration for the keyboard layout widget
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct KeyboardConfig {
+	/// The IBus engines cycled through by clicking the widget, e.g. `xkb:us::eng`
+	pub engines: Vec<String>,
+}
+
+/// Configuration for the unread mail widget
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MailConfig {
+	/// The maildirs whose new mails are counted
+	pub maildirs: Vec<PathBuf>,
+	/// Whether to hide the widget when there is no unread mail
+	pub hide_empty: bool,
+	/// The layout of the widget, with the placeholders `{icon}` and `{value}`
+	pub format: String,
+}
+
+impl Default for MailConfig {
+	fn default() -> Self {
+		MailConfig {
+			maildirs: Vec::new(),
+			hide_empty: true,
+			format: String::from("{icon} {value}"),
+		}
+	}
+}
+
+/// Configuration for the focused window title widget
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WindowConfig {
+	/// The maximum length of the title in characters. Longer titles are cut short.
+	pub max_length: usize,
+}
+
+impl Default for WindowConfig {
+	fn default() -> Self {
+		WindowConfig { max_length: 60 }
+	}
+}
+
+/// Configuration for the flair widget, decorative text that toggles compact mode on click.
+/// Leave `flair` out of `widgets` to turn it off.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FlairConfig {
+	pub text: String,
+	/// The color of the text. The bar's text color is used when unset.
+	pub color: Option<Color>,
+}
+
+impl Default for FlairConfig {
+	fn default() -> Self {
+		FlairConfig { text: String::from("(◕ᴗ◕✿)"), color: None }
+	}
+}
+
+/// Configuration for the buttons widget, a row of launcher buttons running commands on click
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ButtonsConfig {
+	/// The buttons, from left to right. Each has a `text`, e.g. an icon, an optional `color`,
+	/// and shell commands run by clicking it with the `left`, `middle` or `right` button:
+	/// `items = [{ text = "⏻", left = "wlogout" }]`
+	pub items: Vec<ButtonConfig>,
+	/// Put between the buttons
+	pub separator: String,
+}
+
+impl Default for ButtonsConfig {
+	fn default() -> Self {
+		ButtonsConfig { items: Vec::new(), separator: String::from(" ") }
+	}
+}
+
+/// Configuration for the power widget, a button opening a power menu
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PowerConfig {
+	/// A shell command opening a power menu, e.g. `wlogout`. When unset, a click lets you pick
+	/// one of `entries` with zenity or rofi.
+	pub command: Option<String>,
+	/// The choices offered when `command` is unset, as `[label, shell command]` pairs
+	pub entries: Vec<(String, String)>,
+	pub color: Color,
+}
+
+impl Default for PowerConfig {
+	fn default() -> Self {
+		let entry = |label: &str, command: &str| (label.to_owned(), command.to_owned());
+		PowerConfig {
+			command: None,
+			entries: vec![
+				entry("Suspend", "systemctl suspend"),
+				entry("Reboot", "systemctl reboot"),
+				entry("Power off", "systemctl poweroff"),
+			],
+			color: color("#ff5555"),
+		}
+	}
+}
+
+/// A launcher button of the buttons widget
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ButtonConfig {
+	pub text: String,
+	/// The color of the text. The bar's text color is used when unset.
+	pub color: Option<Color>,
+	/// The shell command run on a left click
+	pub left: Option<String>,
+	/// The shell command run on a middle click
+	pub middle: Option<String>,
+	/// The shell command run on a right click
+	pub right: Option<String>,
+}
+
+/// How gauges are drawn, for widgets showing their level as a gauge
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GaugeConfig {
+	/// The width of a gauge in characters
+	pub width: usize,
+	pub style: GaugeStyle,
+	/// The color of an empty gauge. The color moves towards `full_color` as the gauge fills.
+	pub empty_color: Color,
+	pub full_color: Color,
+}
+
+impl Default for GaugeConfig {
+	fn default() -> Self {
+		GaugeConfig {
+			width: 8,
+			style: GaugeStyle::Blocks,
+			empty_color: color("#ff5555"),
+			full_color: color("#55ff55"),
+		}
+	}
+}
+
+/// The glyphs gauges are drawn with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GaugeStyle {
+	/// Block elements, filled in eighths of a character
+	Blocks,
+	/// Shades, filled in thirds of a character
+	Shades,
+	/// `#` and `-`, for fonts without block elements
+	Ascii,
+}
+
+/// How the icons of the widgets are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IconStyle {
+	/// Nerd Font glyphs
+	Glyphs,
+	/// Emoji, for fonts without Nerd Font glyphs
+	Emoji,
+	/// Short text labels, for fonts without Nerd Font glyphs
+	Text,
+}
+
+/// Configuration for the notifications shown while adjusting the volume or brightness
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct OsdConfig {
+	pub enabled: bool,
+	/// The minimum time in milliseconds between two notifications, to not flood the
+	/// notification daemon while scrolling
+	pub throttle_ms: u64,
+}
+
+impl Default for OsdConfig {
+	fn default() -> Self {
+		OsdConfig { enabled: false, throttle_ms: 150 }
+	}
+}
+
+/// Configuration for the warning shown when a widget stops updating
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WatchdogConfig {
+	pub enabled: bool,
+	/// How long an update may take, in seconds, before the widget is considered stalled
+	pub timeout_secs: u64,
+	/// The color of the warning
+	pub color: Color,
+	/// How many refresh intervals a background worker, e.g. the one checking for updates, may
+	/// go without refreshing before it's restarted
+	pub stale_intervals: u32,
+	/// The most times a worker is restarted per hour, so one that keeps failing isn't restarted
+	/// in a tight loop
+	pub max_restarts: u32,
+}
+
+impl Default for WatchdogConfig {
+	fn default() -> Self {
+		WatchdogConfig {
+			enabled: true,
+			timeout_secs: 10,
+			color: color("#ff5555"),
+			stale_intervals: 3,
+			max_restarts: 5,
+		}
+	}
+}
+
+/// Parses a color used as a default value
+fn color(hex: &str) -> Color {
+	hex.parse().expect("invalid default color")
+}
+
+/// Notification urgency level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Urgency {
+	Low,
+	Normal,
+	Critical,
+}
+
+/// Configuration for the network widget
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+	/// Whether to show download and upload rates
+	pub throughput: bool,
+	/// The interface to show rates for. Detected from the active connection when unset.
+	pub interface: Option<String>,
+	pub connected_color: Color,
+	pub disconnected_color: Color,
+	/// The color of the marker shown behind a captive portal
+	pub portal_color: Color,
+	/// The shell command opening the login page of a captive portal. Portals redirect any plain
+	/// HTTP page to their login page.
+	pub portal_command: String,
+	/// When to run `portal_command`
+	pub portal_open: PortalOpen,
+	/// The color of the `!` shown when connected, but without internet access
+	pub limited_color: Color,
+	/// The color of the marker shown when not connected to any network
+	pub offline_color: Color,
+	/// The color of the `?` shown when the internet access can't be checked
+	pub unknown_color: Color,
+	/// Show only the connection icon and status, for narrow screens
+	pub short: bool,
+	/// Whether to show the IPv4 address of the interface
+	pub ipv4: bool,
+	/// Whether to show the IPv6 address of the interface
+	pub ipv6: bool,
+	/// Whether to mark connections NetworkManager considers metered, e.g. mobile hotspots. Adds
+	/// an nmcli call to every update.
+	pub metered: bool,
+	/// Combined download and upload rate in KiB/s above which to send a notification on a
+	/// metered connection, once it's been sustained for `metered_alert_secs`. Works without
+	/// `metered`. Disabled when unset.
+	pub metered_alert: Option<f64>,
+	pub metered_alert_secs: u64,
+}
+
+impl Default for NetworkConfig {
+	fn default() -> Self {
+		NetworkConfig {
+			throughput: false,
+			interface: None,
+			connected_color: color("#99ee99"),
+			disconnected_color: color("#BB5555"),
+			portal_color: color("#ffaa00"),
+			portal_command: String::from("xdg-open http://neverssl.com"),
+			portal_open: PortalOpen::Click,
+			limited_color: color("#ff5555"),
+			offline_color: color("#ff5555"),
+			unknown_color: color("#ffc300"),
+			short: false,
+			ipv4: false,
+			ipv6: false,
+			metered: false,
+			metered_alert: None,
+			metered_alert_secs: 60,
+		}
+	}
+}
+
+/// When the network widget opens the login page of a captive portal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PortalOpen {
+	Never,
+	/// On a left click while behind a portal, in place of expanding the widget
+	Click,
+	/// As soon as a portal is detected, and on a left click
+	Auto,
+}
+
+/// Configuration for the traffic widget, counting the data transferred over the network
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TrafficConfig {
+	/// The layout of the widget, with the placeholders `{icon}`, `{value}` and `{unit}`
+	pub format: String,
+	/// The interface to count. Follows `network.interface`, or the active connection, when unset.
+	pub interface: Option<String>,
+	/// What the data is counted since
+	pub since: TrafficPeriod,
+	/// The number of decimals the data is shown with
+	pub precision: usize,
+	/// The unit the data is shown in
+	pub unit: ByteUnit,
+	pub color: Color,
+}
+
+impl Default for TrafficConfig {
+	fn default() -> Self {
+		TrafficConfig {
+			format: String::from("{icon} {value} {unit} today"),
+			interface: None,
+			since: TrafficPeriod::Midnight,
+			precision: 1,
+			unit: ByteUnit::Binary,
+			color: color("#cccccc"),
+		}
+	}
+}
+
+/// What the traffic widget counts the data since
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrafficPeriod {
+	/// Local midnight. The count is kept in the cache directory, so it survives restarts, but
+	/// data transferred while the bar isn't running is missed.
+	Midnight,
+	/// The interface coming up, as counted by the kernel
+	Boot,
+}
+
+/// The prefix of environment variables overriding configuration values
+const ENV_PREFIX: &str = "AGRABAR_";
+
+/// The tables of the configuration, whose values can be overridden by environment variables
+const SECTIONS: &[&str] = &[
+	"recording",
+	"music",
+	"battery",
+	"network",
+	"traffic",
+	"volume",
+	"output",
+	"microphone",
+	"device_menu",
+	"brightness",
+	"monitor",
+	"clock",
+	"keyboard",
+	"mail",
+	"disk",
+	"load",
+	"sparkline",
+	"memory",
+	"temperature",
+	"updates",
+	"sun",
+	"nightlight",
+	"tasks",
+	"workspaces",
+	"window",
+	"flair",
+	"buttons",
+	"power",
+	"gauge",
+	"events",
+	"osd",
+	"watchdog",
+];
+
+/// Overrides values of the configuration `table` with the environment variables in `vars`. A
+/// variable is named after the path of the value, uppercased and prefixed with `AGRABAR_`, e.g.
+/// `AGRABAR_BATTERY_THRESHOLD=20` for `battery.threshold`, `AGRABAR_MAX_WIDTH=120` for
+/// `max_width`, or `AGRABAR_BLOCKS_CLOCK_MIN_WIDTH=12` for `blocks.clock.min_width`. Values are
+/// read as TOML, and as plain strings if they aren't valid TOML.
+fn apply_env<I: Iterator<Item = (String, String)>>(table: &mut Table, vars: I) {
+	for (name, raw) in vars {
+		let key = match name.strip_prefix(ENV_PREFIX) {
+			Some(key) => key.to_lowercase(),
+			None => continue,
+		};
+		let value = toml::from_str::<Table>(&format!("value = {}", raw))
+			.ok()
+			.and_then(|mut parsed| parsed.remove("value"))
+			.unwrap_or(Value::String(raw));
+		let path = env_path(&key);
+		let (field, tables) = path.split_last().unwrap();
+		let mut table = &mut *table;
+		for name in tables {
+			let entry = table.entry(name.clone()).or_insert_with(|| Value::Table(Table::new()));
+			if !entry.is_table() {
+				*entry = Value::Table(Table::new());
+			}
+			table = entry.as_table_mut().unwrap();
+		}
+		table.insert(field.clone(), value);
+	}
+}
+
+/// The path of the value overridden by the lowercased environment variable name `key`, without
+/// the prefix
+fn env_path(key: &str) -> Vec<String> {
+	// Widget names contain no underscores
+	if let Some((widget, field)) = key.strip_prefix("blocks_").and_then(|key| key.split_once('_')) {
+		return vec![String::from("blocks"), widget.to_owned(), field.to_owned()];
+	}
+	for section in SECTIONS {
+		if let Some(field) = key.strip_prefix(section).and_then(|key| key.strip_prefix('_')) {
+			return vec![(*section).to_owned(), field.to_owned()];
+		}
+	}
+	vec![key.to_owned()]
+}
+
+impl Config {
+	/// The location of the configuration file
+	pub fn path() -> Option<PathBuf> {
+		dirs::config_file()
+	}
+
+	/// Loads the configuration file, using the defaults if it doesn't exist. Values can be
+	/// overridden with environment variables named after them, e.g. `AGRABAR_BATTERY_THRESHOLD`.
+	pub fn load() -> anyhow::Result<Config> {
+		let path = Config::path();
+		let text = match path.as_ref().map(fs::read_to_string) {
+			Some(Ok(text)) => text,
+			Some(Err(e)) if e.kind() == IoErrorKind::NotFound => String::new(),
+			Some(Err(e)) => return Err(e.into()),
+			None => String::new(),
+		};
+		let source =
+			path.map_or_else(|| String::from("configuration"), |path| path.display().to_string());
+		let mut table: Table = toml::from_str(&text).map_err(|e| anyhow!("{}: {}", source, e))?;
+		// Skips variables that aren't unicode, which `env::vars` would panic on
+		let vars = env::vars_os().filter_map(|(name, value)| {
+			Some((name.into_string().ok()?, value.into_string().ok()?))
+		});
+		apply_env(&mut table, vars);
+		Value::Table(table).try_into().map_err(|e| anyhow!("{}: {}", source, e))
+	}
+
+	/// Checks for values that can't be used. Returns a description of every problem found.
+	pub fn validate(&self) -> Vec<String> {
+		let mut errors = Vec::new();
+		for name in &self.widgets {
+			if !WIDGETS.iter().any(|(widget, _)| *widget == name.as_str()) {
+				errors.push(format!("widgets: unknown widget \"{}\"", name));
+			}
+		}
+		for format in &self.clock.formats {
+			if StrftimeItems::new(format).any(|item| item == Item::Error) {
+				errors.push(format!("clock.formats: invalid format \"{}\"", format));
+			}
+		}
+		#[cfg(feature = "pulse")]
+		for pattern in &self.device_menu.hidden {
+			if let Err(e) = regex::Regex::new(pattern) {
+				errors.push(format!("device_menu.hidden: invalid pattern \"{}\": {}", pattern, e));
+			}
+		}
+		let percentages = [
+			("battery.threshold", Some(self.battery.threshold)),
+			("battery.full_level", self.battery.full_level),
+		];
+		for (field, value) in percentages.iter() {
+			if let Some(value) = value {
+				if *value > 100 {
+					errors.push(format!("{}: {} is not a percentage", field, value));
+				}
+			}
+		}
+		let intervals = [
+			("updates.interval_secs", self.updates.interval_secs),
+			("tasks.interval_secs", self.tasks.interval_secs),
+			("monitor.interval_secs", self.monitor.interval_secs),
+		];
+		for (field, secs) in intervals.iter() {
+			if *secs == 0 {
+				errors.push(format!("{}: must be positive", field));
+			}
+		}
+		errors
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn zero_interval() {
+		let error = String::from("updates.interval_secs: must be positive");
+		let mut config = Config::default();
+		assert!(!config.validate().contains(&error));
+		config.updates.interval_secs = 0;
+		assert!(config.validate().contains(&error));
+	}
+
+	#[test]
+	fn sections_cover_every_table() {
+		let defaults = Value::try_from(Config::default()).unwrap();
+		for (name, value) in defaults.as_table().unwrap() {
+			if value.is_table() && name != "blocks" {
+				assert!(SECTIONS.contains(&name.as_str()), "{} is missing from SECTIONS", name);
+			}
+		}
+	}
+}
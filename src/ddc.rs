@@ -0,0 +1,85 @@
+//! Reads and changes the brightness of external monitors over DDC/CI through `ddcutil`
+
+use std::process::Command;
+
+use anyhow::anyhow;
+
+use crate::process;
+
+/// The VCP feature code of the brightness
+const BRIGHTNESS: &str = "10";
+
+/// A monitor ddcutil can control
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Display {
+	/// The number ddcutil refers to the display by
+	pub number: u32,
+	/// The model of the monitor
+	pub name: String,
+}
+
+/// The brightness of a display
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Level {
+	pub current: u32,
+	pub max: u32,
+}
+
+impl Level {
+	/// The brightness in percent
+	pub fn percent(self) -> u32 {
+		self.current * 100 / self.max.max(1)
+	}
+}
+
+fn ddcutil(args: &[&str]) -> anyhow::Result<String> {
+	let output = process::output(Command::new("ddcutil").args(args))
+		.map_err(|e| anyhow!("Couldn't run ddcutil: {}", e))?;
+	match output.status.success() {
+		true => Ok(String::from_utf8_lossy(&output.stdout).into_owned()),
+		false => Err(anyhow!("ddcutil failed: {}", String::from_utf8_lossy(&output.stderr).trim())),
+	}
+}
+
+/// The displays supporting DDC/CI. Empty if ddcutil isn't installed.
+pub fn detect() -> Vec<Display> {
+	let output = ddcutil(&["detect", "--brief"]).unwrap_or_default();
+	let mut displays = Vec::new();
+	// Each display starts with a `Display <number>` line, followed by indented details. Displays
+	// without DDC/CI are listed as `Invalid display`.
+	for line in output.lines() {
+		if let Some(number) = line.strip_prefix("Display ") {
+			if let Ok(number) = number.trim().parse() {
+				displays.push(Display { number, name: format!("Display {}", number) });
+			}
+		} else if let (Some(monitor), Some(display)) =
+			(line.trim().strip_prefix("Monitor:"), displays.last_mut())
+		{
+			// The monitor is given as `manufacturer:model:serial`
+			if let Some(model) = monitor.trim().split(':').nth(1).filter(|model| !model.is_empty())
+			{
+				display.name = model.to_owned();
+			}
+		}
+	}
+	displays
+}
+
+/// The brightness of the display numbered `display`
+pub fn get(display: u32) -> anyhow::Result<Level> {
+	let number = display.to_string();
+	let output = ddcutil(&["--display", &number, "--brief", "getvcp", BRIGHTNESS])?;
+	// Printed as `VCP 10 C <current> <max>`
+	let fields: Vec<&str> = output.split_whitespace().collect();
+	match fields.as_slice() {
+		["VCP", _, "C", current, max] => Ok(Level { current: current.parse()?, max: max.parse()? }),
+		_ => Err(anyhow!("ddcutil printed an unexpected brightness {:?}", output.trim())),
+	}
+}
+
+/// Sets the brightness of the display numbered `display`
+pub fn set(display: u32, value: u32) -> anyhow::Result<()> {
+	let (number, value) = (display.to_string(), value.to_string());
+	ddcutil(&["--display", &number, "setvcp", BRIGHTNESS, &value])?;
+	Ok(())
+}
@@ -0,0 +1,174 @@
+//! The default configuration as a commented TOML file, for users to start their own from. The
+//! comments are the doc comments of the configuration structs, read from their source, so the
+//! file stays in sync with the code.
+
+use std::{
+	fmt::Write,
+	fs::{self, OpenOptions},
+	io::{ErrorKind as IoErrorKind, Write as _},
+	path::PathBuf,
+};
+
+use anyhow::anyhow;
+use toml::{value::Table, Value};
+
+use crate::config::{BlockConfig, Config};
+
+/// The source the documentation of every option is read from
+const SOURCE: &str = include_str!("config.rs");
+
+/// The documentation of a configuration struct
+#[derive(Debug)]
+struct StructDocs<'a> {
+	name: &'a str,
+	doc: Vec<&'a str>,
+	/// The name, type and documentation of every field, in order
+	fields: Vec<(&'a str, &'a str, Vec<&'a str>)>,
+}
+
+/// Reads the documentation of every public struct in `source`
+fn parse(source: &str) -> Vec<StructDocs> {
+	let mut structs: Vec<StructDocs> = Vec::new();
+	let mut doc = Vec::new();
+	let mut inside = false;
+	for line in source.lines() {
+		let trimmed = line.trim();
+		if let Some(comment) = trimmed.strip_prefix("///") {
+			doc.push(comment.strip_prefix(' ').unwrap_or(comment));
+		} else if trimmed.starts_with("#[") {
+			// Attributes sit between the doc comment and the item
+		} else if let Some(name) = line.strip_prefix("pub struct ") {
+			inside = name.ends_with('{');
+			let name = name.trim_end_matches(|c| c == '{' || c == ' ' || c == ';');
+			let name = name.split('(').next().unwrap_or(name);
+			structs.push(StructDocs { name, doc: doc.split_off(0), fields: Vec::new() });
+		} else if inside && line == "}" {
+			inside = false;
+		} else if let (true, Some(field)) = (inside, trimmed.strip_prefix("pub ")) {
+			if let (Some(struct_docs), Some((name, ty))) =
+				(structs.last_mut(), field.split_once(':'))
+			{
+				struct_docs.fields.push((name, ty.trim().trim_end_matches(','), doc.split_off(0)));
+			}
+		} else {
+			doc.clear();
+		}
+	}
+	structs
+}
+
+/// The default configuration, with every option documented. Options without a default are
+/// commented out.
+pub fn commented() -> anyhow::Result<String> {
+	let docs = parse(SOURCE);
+	let config = match Value::try_from(Config::default())? {
+		Value::Table(table) => table,
+		_ => Table::new(),
+	};
+	let mut out = String::new();
+	writeln!(out, "# The agrabar configuration, with the default value of every option")?;
+	write_table(&mut out, &docs, "Config", &config, "")?;
+	Ok(out)
+}
+
+/// Writes the default configuration to the configuration file if there is none yet, so new users
+/// have a file to start from. Returns the path written to, or `None` if the file already exists.
+pub fn scaffold() -> anyhow::Result<Option<PathBuf>> {
+	let path = Config::path().ok_or_else(|| anyhow!("Couldn't find the home directory"))?;
+	if path.exists() {
+		return Ok(None);
+	}
+	if let Some(dir) = path.parent() {
+		fs::create_dir_all(dir)?;
+	}
+	// Never overwrites a file, even one created since the check above
+	let mut file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+		Ok(file) => file,
+		Err(e) if e.kind() == IoErrorKind::AlreadyExists => return Ok(None),
+		Err(e) => return Err(e.into()),
+	};
+	file.write_all(commented()?.as_bytes())?;
+	Ok(Some(path))
+}
+
+/// Writes the fields of the struct `name` with their values in `table`, followed by the tables of
+/// its nested structs. `path` is the key of the table, empty at the top level.
+fn write_table(
+	out: &mut String,
+	docs: &[StructDocs],
+	name: &str,
+	table: &Table,
+	path: &str,
+) -> anyhow::Result<()> {
+	let fields = match docs.iter().find(|docs| docs.name == name) {
+		Some(docs) => &docs.fields,
+		None => return Ok(()),
+	};
+	let mut nested = Vec::new();
+	for (field, ty, doc) in fields {
+		let key = match path {
+			"" => field.to_string(),
+			path => format!("{}.{}", path, field),
+		};
+		if docs.iter().any(|docs| docs.name == *ty) {
+			nested.push((*field, *ty, doc, key));
+			continue;
+		}
+		if let Some(entry) = ty.strip_prefix("HashMap<String, ").and_then(|ty| ty.strip_suffix('>'))
+		{
+			nested.push((*field, entry, doc, key));
+			continue;
+		}
+		writeln!(out)?;
+		write_doc(out, doc)?;
+		match table.get(*field) {
+			Some(value) => writeln!(out, "{}", inline(field, value)?)?,
+			None => writeln!(out, "#{} =", field)?,
+		}
+	}
+	for (field, ty, doc, key) in nested {
+		writeln!(out)?;
+		let struct_doc = docs.iter().find(|docs| docs.name == ty).map(|docs| &docs.doc);
+		write_doc(out, struct_doc.filter(|_| doc.is_empty()).unwrap_or(doc))?;
+		if ty == "BlockConfig" {
+			// Blocks are keyed by widget name, so show an example for one widget
+			writeln!(out, "#[{}.clock]", key)?;
+			let mut example = String::new();
+			let block = match Value::try_from(BlockConfig::default())? {
+				Value::Table(table) => table,
+				_ => Table::new(),
+			};
+			write_table(&mut example, docs, ty, &block, &key)?;
+			for line in example.lines() {
+				match line {
+					"" => writeln!(out)?,
+					line if line.starts_with('#') => writeln!(out, "{}", line)?,
+					line => writeln!(out, "#{}", line)?,
+				}
+			}
+			continue;
+		}
+		writeln!(out, "[{}]", key)?;
+		let empty = Table::new();
+		let table = match table.get(field) {
+			Some(Value::Table(table)) => table,
+			_ => &empty,
+		};
+		write_table(out, docs, ty, table, &key)?;
+	}
+	Ok(())
+}
+
+fn write_doc(out: &mut String, doc: &[&str]) -> anyhow::Result<()> {
+	for line in doc {
+		writeln!(out, "# {}", line)?;
+	}
+	Ok(())
+}
+
+/// Formats `key = value`, letting toml take care of quoting and escaping
+fn inline(key: &str, value: &Value) -> anyhow::Result<String> {
+	let mut table = Table::new();
+	table.insert(key.to_owned(), value.clone());
+	Ok(toml::to_string(&table)?.trim_end().to_owned())
+}
@@ -0,0 +1,27 @@
+//! Where agrabar's files are kept, following the XDG base directory specification
+
+use std::{fs, path::PathBuf};
+
+use anyhow::anyhow;
+use directories::ProjectDirs;
+
+fn project() -> Option<ProjectDirs> {
+	ProjectDirs::from("", "", "agrabar")
+}
+
+/// The configuration file, in `$XDG_CONFIG_HOME/agrabar`, or `~/.config/agrabar` when unset.
+/// `None` if the home directory can't be found.
+pub fn config_file() -> Option<PathBuf> {
+	project().map(|dirs| dirs.config_dir().join("config.toml"))
+}
+
+/// The directory for cached data, in `$XDG_CACHE_HOME/agrabar`, or `~/.cache/agrabar` when
+/// unset. It's created when it doesn't exist yet.
+pub fn cache_dir() -> anyhow::Result<PathBuf> {
+	let dir = project()
+		.map(|dirs| dirs.cache_dir().to_owned())
+		.ok_or_else(|| anyhow!("Couldn't find the home directory"))?;
+	fs::create_dir_all(&dir)
+		.map_err(|e| anyhow!("Couldn't create the cache directory {}: {}", dir.display(), e))?;
+	Ok(dir)
+}
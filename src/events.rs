@@ -0,0 +1,49 @@
+use std::{
+	fs::{File, OpenOptions},
+	io::Write,
+	sync::{Arc, Mutex},
+};
+
+use serde::Serialize;
+
+use crate::config::EventsConfig;
+
+/// A state change reported to external tools
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+	VolumeChanged { volume: u8, muted: bool },
+	BatteryCritical { capacity: u8 },
+	ConnectionChanged { connection: &'a str },
+}
+
+/// A stream of events written as lines of JSON. Does nothing unless enabled in the configuration.
+#[derive(Debug, Clone, Default)]
+pub struct Events {
+	file: Option<Arc<Mutex<File>>>,
+}
+
+impl Events {
+	/// Opens the configured event stream
+	pub fn open(config: &EventsConfig) -> anyhow::Result<Self> {
+		let file = match &config.path {
+			Some(path) => Some(OpenOptions::new().create(true).append(true).open(path)?),
+			None => None,
+		};
+		Ok(Events { file: file.map(|file| Arc::new(Mutex::new(file))) })
+	}
+
+	/// Writes an event to the stream, if enabled
+	pub fn emit(&self, event: Event) {
+		let file = match &self.file {
+			Some(file) => file,
+			None => return,
+		};
+		let result = serde_json::to_string(&event)
+			.map_err(anyhow::Error::from)
+			.and_then(|line| Ok(writeln!(file.lock().unwrap(), "{}", line)?));
+		if let Err(e) = result {
+			eprintln!("Failed to write event: {}", e);
+		}
+	}
+}
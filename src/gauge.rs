@@ -0,0 +1,82 @@
+//! Text progress bars, for widgets showing a level in percent
+
+use unixbar::{bfmt, format::Format};
+
+use crate::config::{GaugeConfig, GaugeStyle};
+
+/// Renders `fraction`, from 0.0 to 1.0, as a bar `width` characters wide
+pub fn gauge(fraction: f64, width: usize, style: GaugeStyle) -> String {
+	gauge_with(fraction, width, style.glyphs())
+}
+
+/// Renders `fraction` as a bar made of `glyphs`, which go from an empty to a full cell. Glyphs in
+/// between show partially filled cells.
+pub fn gauge_with(fraction: f64, width: usize, glyphs: &[char]) -> String {
+	let steps = glyphs.len().saturating_sub(1);
+	if steps == 0 {
+		return String::new();
+	}
+	let fraction = if fraction.is_nan() { 0.0 } else { fraction.max(0.0).min(1.0) };
+	// The filled part, in partial steps
+	let filled = (fraction * (width * steps) as f64).round() as usize;
+	(0..width).map(|cell| glyphs[filled.saturating_sub(cell * steps).min(steps)]).collect()
+}
+
+/// Appends a gauge for `fraction` to `format`, colored by how full it is
+pub fn append(format: Format, fraction: f64, config: &GaugeConfig) -> Format {
+	let color = config.empty_color.mix(&config.full_color, fraction);
+	let text = gauge(fraction, config.width, config.style);
+	let gauge = bfmt![fg[color.as_str()] fmt[" {}", text]];
+	Format::Concat(vec![Box::new(format), Box::new(gauge)])
+}
+
+impl GaugeStyle {
+	/// The glyphs used for the style, from empty to full
+	pub fn glyphs(self) -> &'static [char] {
+		match self {
+			GaugeStyle::Blocks => &[' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'],
+			GaugeStyle::Shades => &['░', '▒', '▓', '█'],
+			GaugeStyle::Ascii => &['-', '#'],
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn empty_half_full() {
+		assert_eq!(gauge(0.0, 4, GaugeStyle::Blocks), "    ");
+		assert_eq!(gauge(0.5, 4, GaugeStyle::Blocks), "██  ");
+		assert_eq!(gauge(1.0, 4, GaugeStyle::Blocks), "████");
+		assert_eq!(gauge(0.0, 4, GaugeStyle::Ascii), "----");
+		assert_eq!(gauge(0.5, 4, GaugeStyle::Ascii), "##--");
+		assert_eq!(gauge(1.0, 4, GaugeStyle::Ascii), "####");
+	}
+
+	#[test]
+	fn partial_cells() {
+		// 1.5 of 4 cells, the second one half full
+		assert_eq!(gauge(0.375, 4, GaugeStyle::Blocks), "█▌  ");
+		// Rounded to the nearest eighth
+		assert_eq!(gauge(0.05, 1, GaugeStyle::Blocks), " ");
+		assert_eq!(gauge(0.1, 1, GaugeStyle::Blocks), "▏");
+		assert_eq!(gauge(0.2, 1, GaugeStyle::Blocks), "▎");
+		assert_eq!(gauge(0.95, 1, GaugeStyle::Blocks), "█");
+		// Shades fill in thirds, so half a cell rounds up to two of them
+		assert_eq!(gauge(0.5, 3, GaugeStyle::Shades), "█▓░");
+		assert_eq!(gauge(0.45, 3, GaugeStyle::Shades), "█▒░");
+		// Half a cell of a two glyph style rounds up
+		assert_eq!(gauge(0.125, 4, GaugeStyle::Ascii), "#---");
+	}
+
+	#[test]
+	fn out_of_range() {
+		assert_eq!(gauge(-0.5, 4, GaugeStyle::Ascii), "----");
+		assert_eq!(gauge(1.5, 4, GaugeStyle::Ascii), "####");
+		assert_eq!(gauge(f64::NAN, 4, GaugeStyle::Ascii), "----");
+		assert_eq!(gauge_with(0.5, 4, &['#']), "");
+		assert_eq!(gauge(0.5, 0, GaugeStyle::Blocks), "");
+	}
+}
@@ -0,0 +1,40 @@
+//! Detects what the machine has, to leave out the widgets for hardware it lacks
+
+use std::fs;
+
+/// The hardware found at startup
+#[derive(Debug, Clone, Copy)]
+pub struct Hardware {
+	/// Whether there is a battery, e.g. on a laptop
+	pub battery: bool,
+	/// Whether the screen brightness can be adjusted
+	pub backlight: bool,
+}
+
+impl Hardware {
+	/// Looks for batteries and backlights in sysfs, and logs what was found
+	pub fn detect() -> Self {
+		let hardware = Hardware {
+			battery: any_device("/sys/class/power_supply", |name| name.starts_with("BAT")),
+			backlight: any_device("/sys/class/backlight", |_| true),
+		};
+		let found = |present| if present { "found" } else { "not found" };
+		eprintln!(
+			"Detected a {}: battery {}, backlight {}",
+			if hardware.battery { "laptop" } else { "desktop" },
+			found(hardware.battery),
+			found(hardware.backlight)
+		);
+		hardware
+	}
+}
+
+/// Whether the sysfs class directory `class` has a device whose name matches
+fn any_device<F: Fn(&str) -> bool>(class: &str, matches: F) -> bool {
+	match fs::read_dir(class) {
+		Ok(entries) => entries
+			.filter_map(Result::ok)
+			.any(|entry| matches(&entry.file_name().to_string_lossy())),
+		Err(_) => false,
+	}
+}
@@ -0,0 +1,103 @@
+//! The icons shown by the widgets, by what they stand for. Each has a Nerd Font glyph, an emoji
+//! for standard fonts and a short text label, for fonts without the glyphs, where they'd show as
+//! boxes.
+
+use crate::config::IconStyle;
+
+/// An icon, drawn as a glyph, an emoji or a text label depending on the icon style
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Icon {
+	pub glyph: &'static str,
+	pub emoji: &'static str,
+	pub label: &'static str,
+}
+
+const fn icon(glyph: &'static str, emoji: &'static str, label: &'static str) -> Icon {
+	Icon { glyph, emoji, label }
+}
+
+pub const CLOCK: Icon = icon("\u{f017}", "🕒", "TIME");
+pub const DISK: Icon = icon("\u{f0a0}", "💾", "DISK");
+pub const LOAD: Icon = icon("\u{f0e4}", "⚙", "LOAD");
+pub const MEMORY: Icon = icon("\u{f2db}", "🧠", "MEM");
+pub const MAIL: Icon = icon("\u{f0e0}", "📧", "MAIL");
+pub const TASKS: Icon = icon("\u{f0ae}", "📋", "TASK");
+pub const UPDATES: Icon = icon("\u{f021}", "🔄", "UPD");
+pub const SUN: Icon = icon("\u{f185}", "🌅", "SUN");
+/// Shown while the night light is on, and the sun while it's off
+pub const NIGHT_LIGHT: Icon = icon("\u{f186}", "🌙", "NIGHT");
+/// Shown in place of a widget that failed to update
+pub const ERROR: Icon = icon("\u{f06a}", "❗", "ERR");
+pub const STALLED: Icon = icon("\u{f071}", "⚠", "STALL");
+/// Shown in front of widgets whose scroll actions are locked
+pub const LOCK: Icon = icon("\u{f023}", "🔒", "LOCK");
+pub const KEYBOARD: Icon = icon("⌨", "⌨", "KB");
+pub const MICROPHONE: Icon = icon("🎤", "🎤", "MIC");
+/// Shown while a recording is in progress
+pub const RECORDING: Icon = icon("●", "🔴", "●");
+pub const BRIGHTNESS: Icon = icon("☀", "🔆", "BRI");
+/// The brightness of an external monitor
+pub const MONITOR: Icon = icon("\u{f108}", "🖥", "MON");
+pub const PLAYING: Icon = icon("\u{f04b}", "▶", "PLAY");
+pub const PAUSED: Icon = icon("\u{f04c}", "⏸", "PAUSE");
+pub const ETHERNET: Icon = icon("\u{f0e8}", "🔌", "ETH");
+pub const WIRELESS: Icon = icon("\u{f1eb}", "📶", "WIFI");
+/// Shown behind a captive portal
+pub const PORTAL: Icon = icon("\u{f0ac}", "🌐", "PORTAL");
+/// Shown when not connected to any network
+pub const OFFLINE: Icon = icon("\u{f127}", "⛔", "OFFLINE");
+/// The power widget's button
+pub const POWER: Icon = icon("\u{f011}", "⏻", "PWR");
+/// Shown on metered connections
+pub const METERED: Icon = icon("\u{f155}", "💲", "$");
+/// Shown by the traffic widget
+pub const TRAFFIC: Icon = icon("\u{f0ec}", "🔃", "DATA");
+/// A connection of another kind, or none
+pub const NETWORK_UNKNOWN: Icon = icon("\u{f1eb}\u{fe56}", "❓", "NET?");
+/// Shown in `volume.muted_format`
+pub const MUTED: Icon = icon("🔇", "🔇", "VOL");
+/// Volume icons from quiet to loud, each covering an equal share of the range
+pub const VOLUME: [Icon; 3] =
+	[icon("\u{f026}", "🔈", "VOL"), icon("\u{f027}", "🔉", "VOL"), icon("\u{f028}", "🔊", "VOL")];
+/// Battery icons from empty to full, each covering an equal share of the range
+pub const BATTERY: [Icon; 5] = [
+	icon("\u{f244}", "🪫", "BAT"),
+	icon("\u{f243}", "🔋", "BAT"),
+	icon("\u{f242}", "🔋", "BAT"),
+	icon("\u{f241}", "🔋", "BAT"),
+	icon("\u{f240}", "🔋", "BAT"),
+];
+/// Shown in front of the battery icon while charging
+pub const CHARGING: Icon = icon("\u{f0e7}", "⚡", "+");
+/// Shown instead of the charging icon when charging has reached its limit
+pub const LIMITED: Icon = icon("\u{f023}", "🔒", "=");
+/// Thermometers from cool to hot, for below 60, 70, 80, 90 and from 90 °C
+pub const TEMPERATURE: [Icon; 5] = [
+	icon("\u{f2cb}", "🌡", "TEMP"),
+	icon("\u{f2ca}", "🌡", "TEMP"),
+	icon("\u{f2c9}", "🌡", "TEMP"),
+	icon("\u{f2c8}", "🌡", "TEMP"),
+	icon("\u{f2c7}", "🌡", "TEMP"),
+];
+/// The phases of the moon, starting at the new moon
+pub const MOON: [Icon; 8] = [
+	icon("🌑", "🌑", "NEW"),
+	icon("🌒", "🌒", "WAX"),
+	icon("🌓", "🌓", "FQ"),
+	icon("🌔", "🌔", "WAX"),
+	icon("🌕", "🌕", "FULL"),
+	icon("🌖", "🌖", "WANE"),
+	icon("🌗", "🌗", "LQ"),
+	icon("🌘", "🌘", "WANE"),
+];
+
+impl IconStyle {
+	/// How `icon` is drawn in this style
+	pub fn pick(self, icon: Icon) -> &'static str {
+		match self {
+			IconStyle::Glyphs => icon.glyph,
+			IconStyle::Emoji => icon.emoji,
+			IconStyle::Text => icon.label,
+		}
+	}
+}
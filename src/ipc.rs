@@ -0,0 +1,164 @@
+//! A client for the i3 IPC protocol, which sway speaks as well
+
+use std::{
+	convert::TryInto,
+	env,
+	io::{Read, Write},
+	os::unix::net::UnixStream,
+	process::Command,
+};
+
+use anyhow::anyhow;
+use serde::{de::DeserializeOwned, Deserialize};
+
+const MAGIC: &[u8] = b"i3-ipc";
+const RUN_COMMAND: u32 = 0;
+const GET_WORKSPACES: u32 = 1;
+const SUBSCRIBE: u32 = 2;
+const GET_TREE: u32 = 4;
+
+/// A workspace, as listed by the window manager
+#[derive(Debug, Clone, Deserialize)]
+pub struct Workspace {
+	pub name: String,
+	/// Whether the workspace has the keyboard focus
+	pub focused: bool,
+	/// Whether the workspace is shown on an output
+	pub visible: bool,
+	/// Whether a window on the workspace wants attention
+	pub urgent: bool,
+}
+
+/// A connection to the i3 or sway IPC socket
+pub struct Connection {
+	stream: UnixStream,
+}
+
+impl Connection {
+	/// Connects to the running window manager, found through `$SWAYSOCK`, `$I3SOCK`, or by asking
+	/// i3
+	pub fn open() -> anyhow::Result<Self> {
+		let path = match env::var("SWAYSOCK").or_else(|_| env::var("I3SOCK")) {
+			Ok(path) => path,
+			Err(_) => {
+				let output = Command::new("i3").arg("--get-socketpath").output()?;
+				String::from_utf8_lossy(&output.stdout).trim().to_owned()
+			}
+		};
+		if path.is_empty() {
+			return Err(anyhow!("Neither i3 nor sway is running"));
+		}
+		Ok(Connection { stream: UnixStream::connect(path)? })
+	}
+
+	fn send(&mut self, kind: u32, payload: &str) -> anyhow::Result<()> {
+		let mut message = MAGIC.to_vec();
+		message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+		message.extend_from_slice(&kind.to_ne_bytes());
+		message.extend_from_slice(payload.as_bytes());
+		self.stream.write_all(&message)?;
+		Ok(())
+	}
+
+	/// Reads the next message. Returns its type and payload.
+	fn receive(&mut self) -> anyhow::Result<(u32, Vec<u8>)> {
+		let mut header = [0; 14];
+		self.stream.read_exact(&mut header)?;
+		if &header[..6] != MAGIC {
+			return Err(anyhow!("Invalid IPC message"));
+		}
+		let len = u32::from_ne_bytes(header[6..10].try_into()?);
+		let kind = u32::from_ne_bytes(header[10..14].try_into()?);
+		let mut payload = vec![0; len as usize];
+		self.stream.read_exact(&mut payload)?;
+		Ok((kind, payload))
+	}
+
+	/// Sends a request, and parses the reply
+	fn request<T: DeserializeOwned>(&mut self, kind: u32, payload: &str) -> anyhow::Result<T> {
+		self.send(kind, payload)?;
+		let (_, reply) = self.receive()?;
+		Ok(serde_json::from_slice(&reply)?)
+	}
+
+	/// Runs a command, e.g. `workspace 2`
+	pub fn command(&mut self, command: &str) -> anyhow::Result<()> {
+		#[derive(Deserialize)]
+		struct Outcome {
+			success: bool,
+			error: Option<String>,
+		}
+		let outcomes: Vec<Outcome> = self.request(RUN_COMMAND, command)?;
+		match outcomes.into_iter().find(|outcome| !outcome.success) {
+			Some(failed) => Err(anyhow!(failed.error.unwrap_or_else(|| command.to_owned()))),
+			None => Ok(()),
+		}
+	}
+
+	pub fn workspaces(&mut self) -> anyhow::Result<Vec<Workspace>> {
+		self.request(GET_WORKSPACES, "")
+	}
+
+	/// The title of the window with the keyboard focus, or `None` if an empty workspace is
+	/// focused
+	pub fn focused_title(&mut self) -> anyhow::Result<Option<String>> {
+		let tree: Node = self.request(GET_TREE, "")?;
+		let window = tree.focused().filter(|node| node.kind != "workspace");
+		Ok(window.and_then(|node| node.name.clone()))
+	}
+
+	/// Subscribes to `events`, e.g. `"workspace"`. The connection then only receives events.
+	pub fn subscribe(mut self, events: &[&str]) -> anyhow::Result<Subscription> {
+		#[derive(Deserialize)]
+		struct Reply {
+			success: bool,
+		}
+		let reply: Reply = self.request(SUBSCRIBE, &serde_json::to_string(events)?)?;
+		if !reply.success {
+			return Err(anyhow!("Couldn't subscribe to {}", events.join(", ")));
+		}
+		Ok(Subscription { connection: self })
+	}
+}
+
+/// A connection receiving events
+pub struct Subscription {
+	connection: Connection,
+}
+
+impl Subscription {
+	/// Waits for the next event
+	pub fn next(&mut self) -> anyhow::Result<serde_json::Value> {
+		let (_, payload) = self.connection.receive()?;
+		Ok(serde_json::from_slice(&payload)?)
+	}
+}
+
+/// A node of the layout tree: an output, workspace, or container
+#[derive(Debug, Deserialize)]
+struct Node {
+	#[serde(rename = "type")]
+	kind: String,
+	name: Option<String>,
+	focused: bool,
+	#[serde(default)]
+	nodes: Vec<Node>,
+	#[serde(default)]
+	floating_nodes: Vec<Node>,
+}
+
+impl Node {
+	/// The focused node in the tree below this node
+	fn focused(&self) -> Option<&Node> {
+		if self.focused {
+			return Some(self);
+		}
+		self.nodes.iter().chain(&self.floating_nodes).find_map(Node::focused)
+	}
+}
+
+/// Moves to the workspace called `name`
+pub fn switch(name: &str) -> anyhow::Result<()> {
+	let name = name.replace('\\', "\\\\").replace('"', "\\\"");
+	Connection::open()?.command(&format!("workspace \"{}\"", name))
+}
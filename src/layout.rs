@@ -0,0 +1,70 @@
+use std::sync::Mutex;
+
+/// Keeps the bar within a maximum width by collapsing widgets to their compact variants, lowest
+/// priority first. Every widget reports its width whenever it renders.
+#[derive(Debug, Default)]
+pub struct Layout {
+	max_width: Option<usize>,
+	entries: Mutex<Vec<Entry>>,
+}
+
+#[derive(Debug)]
+struct Entry {
+	priority: i32,
+	/// The width of the last rendered output
+	shown: usize,
+	/// The widths of the full and compact variants, if the widget has a compact variant
+	variants: Option<(usize, usize)>,
+}
+
+impl Layout {
+	/// Creates a layout fitting widgets in `max_width` characters, or any width when unset
+	pub fn new(max_width: Option<usize>) -> Self {
+		Layout { max_width, entries: Mutex::default() }
+	}
+
+	/// Adds a widget to the layout. Returns the slot it reports its widths to.
+	pub fn add(&self, priority: i32) -> usize {
+		let mut entries = self.entries.lock().unwrap();
+		entries.push(Entry { priority, shown: 0, variants: None });
+		entries.len() - 1
+	}
+
+	/// Records the width of the output of the widget in `slot`
+	pub fn show(&self, slot: usize, width: usize) {
+		if let Some(entry) = self.entries.lock().unwrap().get_mut(slot) {
+			entry.shown = width;
+		}
+	}
+
+	/// Records the widths of the full and compact variants of the widget in `slot`, and returns
+	/// whether it has to be collapsed to fit
+	pub fn fit(&self, slot: usize, full: usize, compact: usize) -> bool {
+		let mut entries = self.entries.lock().unwrap();
+		if let Some(entry) = entries.get_mut(slot) {
+			entry.variants = Some((full, compact));
+		}
+		let max_width = match self.max_width {
+			Some(max_width) => max_width,
+			None => return false,
+		};
+		let mut width: usize =
+			entries.iter().map(|entry| entry.variants.map_or(entry.shown, |(full, _)| full)).sum();
+		// Collapse widgets from the lowest priority until everything fits
+		let mut collapsible: Vec<usize> =
+			(0..entries.len()).filter(|&i| entries[i].variants.is_some()).collect();
+		collapsible.sort_by_key(|&i| entries[i].priority);
+		for i in collapsible {
+			if width <= max_width {
+				return false;
+			}
+			if let Some((full, compact)) = entries[i].variants {
+				width -= full.saturating_sub(compact);
+			}
+			if i == slot {
+				return true;
+			}
+		}
+		false
+	}
+}
@@ -0,0 +1,56 @@
+// bars - Copyright © Amanda Graven 2021
+//
+// Licensed under the EUPL, Version 1.2 or – as soon they will be approved by
+// the European Commission - subsequent versions of the EUPL (the "Licence");
+// You may not use this work except in compliance with the Licence.
+// You may obtain a copy of the Licence at:
+//
+// https://joinup.ec.europa.eu/software/page/eupl5
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the Licence is distributed on an "AS IS" basis, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// Licence for the specific language governing permissions and limitations under
+// the Licence.
+
+#[cfg(feature = "music")]
+pub mod art;
+pub mod block;
+pub mod builder;
+pub mod cache;
+pub mod clipboard;
+pub mod color;
+pub mod config;
+pub mod ddc;
+pub mod defaults;
+pub mod dirs;
+pub mod events;
+pub mod gauge;
+pub mod hardware;
+pub mod icons;
+pub mod ipc;
+pub mod layout;
+#[cfg(feature = "music")]
+pub mod mpris;
+pub mod notify;
+pub mod osd;
+pub mod picker;
+#[cfg(feature = "music")]
+pub mod playerctl;
+pub mod process;
+pub mod sampler;
+pub mod samples;
+pub mod selftest;
+pub mod sun;
+pub mod template;
+pub mod threshold;
+pub mod units;
+#[cfg(feature = "upower")]
+pub mod upower;
+pub mod version;
+#[cfg(any(feature = "alsa", feature = "pulse"))]
+pub mod volume;
+pub mod watchdog;
+pub mod widgets;
+
+pub use builder::BarBuilder;
@@ -0,0 +1,167 @@
+use std::{
+	sync::{Arc, Mutex},
+	time::Duration,
+};
+
+use dbus::{
+	arg::{prop_cast, PropMap},
+	blocking::{stdintf::org_freedesktop_dbus::Properties, Connection},
+};
+
+use crate::{config::MusicBackend, picker::Picker, playerctl};
+
+const PREFIX: &str = "org.mpris.MediaPlayer2.";
+const PATH: &str = "/org/mpris/MediaPlayer2";
+const ROOT_IFACE: &str = "org.mpris.MediaPlayer2";
+const PLAYER_IFACE: &str = "org.mpris.MediaPlayer2.Player";
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+/// The bus name of the player chosen by the user, if any. When unset, a player is selected
+/// automatically.
+pub type Selection = Arc<Mutex<Option<String>>>;
+
+/// The currently playing or paused song of a player
+pub struct Song {
+	pub artist: String,
+	pub album: String,
+	pub title: String,
+	/// The position of the song on its album
+	pub track: Option<i32>,
+	pub playing: bool,
+	/// The playback speed, where 1.0 is normal
+	pub rate: f64,
+	/// Where the album art can be found, usually a `file://` or `https://` URL
+	pub art_url: Option<String>,
+}
+
+/// A media player reachable over MPRIS
+pub struct Player {
+	pub bus_name: String,
+	/// How the player is talked to
+	pub backend: MusicBackend,
+}
+
+impl Player {
+	/// Lists all media players on the session bus
+	pub fn list(backend: MusicBackend) -> anyhow::Result<Vec<Player>> {
+		if backend == MusicBackend::Playerctl {
+			return Ok(playerctl::players()?
+				.into_iter()
+				.map(|name| Player { bus_name: format!("{}{}", PREFIX, name), backend })
+				.collect());
+		}
+		let conn = Connection::new_session()?;
+		let proxy = conn.with_proxy("org.freedesktop.DBus", "/", TIMEOUT);
+		let (names,): (Vec<String>,) =
+			proxy.method_call("org.freedesktop.DBus", "ListNames", ())?;
+		Ok(names
+			.into_iter()
+			.filter(|name| name.starts_with(PREFIX))
+			.map(|bus_name| Player { bus_name, backend })
+			.collect())
+	}
+
+	/// The name playerctl knows the player by, which is its bus name without the prefix
+	fn name(&self) -> &str {
+		self.bus_name.trim_start_matches(PREFIX)
+	}
+
+	/// The human readable name of the player, falling back to its bus name
+	pub fn identity(&self) -> String {
+		if self.backend == MusicBackend::Playerctl {
+			return self.name().to_owned();
+		}
+		let get = || -> anyhow::Result<String> {
+			let conn = Connection::new_session()?;
+			let proxy = conn.with_proxy(self.bus_name.as_str(), PATH, TIMEOUT);
+			Ok(proxy.get(ROOT_IFACE, "Identity")?)
+		};
+		get().unwrap_or_else(|_| self.name().to_owned())
+	}
+
+	/// Whether the player is currently playing
+	pub fn is_playing(&self) -> bool {
+		match self.backend {
+			MusicBackend::Mpris => self.status().map(|status| status == "Playing").unwrap_or(false),
+			MusicBackend::Playerctl => {
+				playerctl::song(self.name()).ok().flatten().map_or(false, |song| song.playing)
+			}
+		}
+	}
+
+	fn status(&self) -> anyhow::Result<String> {
+		let conn = Connection::new_session()?;
+		let proxy = conn.with_proxy(self.bus_name.as_str(), PATH, TIMEOUT);
+		Ok(proxy.get(PLAYER_IFACE, "PlaybackStatus")?)
+	}
+
+	/// The current song, or `None` if playback is stopped
+	pub fn song(&self) -> anyhow::Result<Option<Song>> {
+		if self.backend == MusicBackend::Playerctl {
+			return playerctl::song(self.name());
+		}
+		let playing = match self.status()?.as_str() {
+			"Playing" => true,
+			"Paused" => false,
+			_ => return Ok(None),
+		};
+		let conn = Connection::new_session()?;
+		let proxy = conn.with_proxy(self.bus_name.as_str(), PATH, TIMEOUT);
+		let metadata: PropMap = proxy.get(PLAYER_IFACE, "Metadata")?;
+		let artist = prop_cast::<Vec<String>>(&metadata, "xesam:artist")
+			.map(|artists| artists.join(", "))
+			.unwrap_or_default();
+		let album = prop_cast::<String>(&metadata, "xesam:album").cloned().unwrap_or_default();
+		let title = prop_cast::<String>(&metadata, "xesam:title").cloned().unwrap_or_default();
+		let track = prop_cast::<i32>(&metadata, "xesam:trackNumber").copied();
+		let art_url = prop_cast::<String>(&metadata, "mpris:artUrl").cloned();
+		// Players that can't change speed may not have the property
+		let rate = proxy.get(PLAYER_IFACE, "Rate").unwrap_or(1.0);
+		Ok(Some(Song { artist, album, title, track, playing, rate, art_url }))
+	}
+
+	/// Calls a method without arguments on the player interface, e.g. `"PlayPause"`
+	pub fn call(&self, method: &str) -> anyhow::Result<()> {
+		if self.backend == MusicBackend::Playerctl {
+			return playerctl::call(self.name(), method);
+		}
+		let conn = Connection::new_session()?;
+		let proxy = conn.with_proxy(self.bus_name.as_str(), PATH, TIMEOUT);
+		proxy.method_call::<(), _, _, _>(PLAYER_IFACE, method, ())?;
+		Ok(())
+	}
+}
+
+/// Picks the player to control out of `players`. The user's choice is used if that player is
+/// still running, otherwise the selection is reset, and the first playing player is used.
+pub fn resolve<'a>(selection: &Selection, players: &'a [Player]) -> Option<&'a Player> {
+	let mut selected = selection.lock().unwrap();
+	if let Some(name) = selected.as_ref() {
+		match players.iter().find(|player| &player.bus_name == name) {
+			Some(player) => return Some(player),
+			// The chosen player has closed, revert to automatic selection
+			None => *selected = None,
+		}
+	}
+	players.iter().find(|player| player.is_playing()).or_else(|| players.first())
+}
+
+/// Calls `method` on the currently controlled player
+pub fn control(backend: MusicBackend, selection: &Selection, method: &str) -> anyhow::Result<()> {
+	let players = Player::list(backend)?;
+	match resolve(selection, &players) {
+		Some(player) => player.call(method),
+		None => Ok(()),
+	}
+}
+
+/// Lets the user choose which player to control for the rest of the session
+pub fn choose(backend: MusicBackend, selection: &Selection) -> anyhow::Result<()> {
+	let players = Player::list(backend)?;
+	let entries: Vec<(String, String)> =
+		players.iter().map(|player| (player.bus_name.clone(), player.identity())).collect();
+	if let Some(name) = Picker::detect().pick("Choose a media player", &entries)? {
+		*selection.lock().unwrap() = Some(name);
+	}
+	Ok(())
+}
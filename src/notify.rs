@@ -0,0 +1,119 @@
+//! Desktop notifications. They're shown through libnotify, or only logged when built without
+//! the `notify` feature, for setups without a notification daemon.
+
+use crate::config::Urgency;
+
+/// A notification, sent with the notifier the crate was built with
+#[derive(Debug, Clone)]
+pub struct Notification {
+	pub summary: String,
+	pub body: Option<String>,
+	pub icon: Option<String>,
+	pub urgency: Urgency,
+	/// A level in percent, which some notification daemons draw as a gauge
+	pub value: Option<i32>,
+	/// Notifications with the same tag replace each other rather than stacking up
+	pub tag: Option<String>,
+}
+
+impl Notification {
+	pub fn new(summary: &str, body: Option<&str>, icon: Option<&str>) -> Self {
+		Notification {
+			summary: summary.to_owned(),
+			body: body.map(String::from),
+			icon: icon.map(String::from),
+			urgency: Urgency::Normal,
+			value: None,
+			tag: None,
+		}
+	}
+
+	pub fn urgency(mut self, urgency: Urgency) -> Self {
+		self.urgency = urgency;
+		self
+	}
+
+	pub fn value(mut self, value: i32) -> Self {
+		self.value = Some(value);
+		self
+	}
+
+	pub fn tag(mut self, tag: &str) -> Self {
+		self.tag = Some(tag.to_owned());
+		self
+	}
+
+	/// Sends the notification
+	pub fn show(&self) -> anyhow::Result<()> {
+		notifier().send(self)
+	}
+}
+
+/// Something notifications can be sent through
+pub trait Notifier: Send + Sync {
+	fn send(&self, notification: &Notification) -> anyhow::Result<()>;
+}
+
+/// Shows notifications on the desktop through libnotify
+#[cfg(feature = "notify")]
+pub struct LibNotify;
+
+#[cfg(feature = "notify")]
+impl Notifier for LibNotify {
+	fn send(&self, notification: &Notification) -> anyhow::Result<()> {
+		use glib::ToVariant;
+
+		let notif = libnotify::Notification::new(
+			&notification.summary,
+			notification.body.as_deref(),
+			notification.icon.as_deref(),
+		);
+		notif.set_urgency(match notification.urgency {
+			Urgency::Low => libnotify::Urgency::Low,
+			Urgency::Normal => libnotify::Urgency::Normal,
+			Urgency::Critical => libnotify::Urgency::Critical,
+		});
+		if let Some(value) = notification.value {
+			notif.set_hint("value", Some(value.to_variant()));
+		}
+		if let Some(tag) = &notification.tag {
+			notif.set_hint("x-canonical-private-synchronous", Some(tag.to_variant()));
+		}
+		notif.show()?;
+		Ok(())
+	}
+}
+
+/// Prints notifications to stderr instead of showing them
+pub struct Log;
+
+impl Notifier for Log {
+	fn send(&self, notification: &Notification) -> anyhow::Result<()> {
+		match &notification.body {
+			Some(body) => {
+				eprintln!("{:?}: {}: {}", notification.urgency, notification.summary, body)
+			}
+			None => eprintln!("{:?}: {}", notification.urgency, notification.summary),
+		}
+		Ok(())
+	}
+}
+
+/// The notifier the crate was built with
+#[cfg(feature = "notify")]
+pub fn notifier() -> &'static dyn Notifier {
+	&LibNotify
+}
+
+/// The notifier the crate was built with
+#[cfg(not(feature = "notify"))]
+pub fn notifier() -> &'static dyn Notifier {
+	&Log
+}
+
+/// Prepares sending notifications, before any are sent
+pub fn init() -> anyhow::Result<()> {
+	#[cfg(feature = "notify")]
+	libnotify::init(env!("CARGO_PKG_NAME")).map_err(|e| anyhow::anyhow!(e))?;
+	Ok(())
+}
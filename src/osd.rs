@@ -0,0 +1,84 @@
+use std::{
+	sync::{Arc, Mutex},
+	thread,
+	time::{Duration, Instant},
+};
+
+use crate::{config::OsdConfig, notify::Notification};
+
+/// A notification showing a level while it's being adjusted, e.g. the volume. Each notification
+/// replaces the previous one, and at most one is sent per throttle interval. Levels set in
+/// between are coalesced, and the latest one is shown once the interval has passed.
+#[derive(Clone)]
+pub struct Osd {
+	summary: &'static str,
+	icon: &'static str,
+	interval: Duration,
+	enabled: bool,
+	state: Arc<Mutex<Pending>>,
+}
+
+#[derive(Default)]
+struct Pending {
+	/// When the last notification was sent
+	sent: Option<Instant>,
+	/// The latest level not shown yet
+	level: Option<u8>,
+}
+
+impl Osd {
+	/// Creates an on-screen display titled `summary`
+	pub fn new(config: &OsdConfig, summary: &'static str, icon: &'static str) -> Self {
+		Osd {
+			summary,
+			icon,
+			interval: Duration::from_millis(config.throttle_ms),
+			enabled: config.enabled,
+			state: Arc::default(),
+		}
+	}
+
+	/// Shows `level` in percent, if enabled
+	pub fn show(&self, level: u8) {
+		if !self.enabled {
+			return;
+		}
+		let mut state = self.state.lock().unwrap();
+		let elapsed = state.sent.map(|sent| sent.elapsed());
+		match elapsed {
+			Some(elapsed) if elapsed < self.interval => {
+				// A notification is already scheduled if a level is pending
+				if state.level.replace(level).is_none() {
+					let osd = self.clone();
+					let wait = self.interval - elapsed;
+					thread::spawn(move || {
+						thread::sleep(wait);
+						let mut state = osd.state.lock().unwrap();
+						if let Some(level) = state.level.take() {
+							state.sent = Some(Instant::now());
+							drop(state);
+							osd.send(level);
+						}
+					});
+				}
+			}
+			_ => {
+				state.sent = Some(Instant::now());
+				state.level = None;
+				drop(state);
+				self.send(level);
+			}
+		}
+	}
+
+	fn send(&self, level: u8) {
+		let body = format!("{}%", level);
+		// Lets notification daemons draw a gauge, and replace the previous notification
+		let notif = Notification::new(self.summary, Some(body.as_str()), Some(self.icon))
+			.value(i32::from(level))
+			.tag(self.summary);
+		if let Err(e) = notif.show() {
+			eprintln!("Failed to show {} notification: {}", self.summary, e);
+		}
+	}
+}
@@ -0,0 +1,101 @@
+use std::{
+	io::{ErrorKind as IoErrorKind, Write},
+	process::{Command, Stdio},
+};
+
+use anyhow::anyhow;
+
+/// A graphical program used to choose one entry out of a list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Picker {
+	Zenity,
+	Rofi,
+}
+
+impl Picker {
+	/// The name of the picker's executable
+	pub fn program(self) -> &'static str {
+		match self {
+			Picker::Zenity => "zenity",
+			Picker::Rofi => "rofi",
+		}
+	}
+
+	/// Finds an installed picker, preferring zenity
+	pub fn detect() -> Self {
+		match Command::new("zenity").arg("--version").output() {
+			Ok(_) => Picker::Zenity,
+			Err(_) => Picker::Rofi,
+		}
+	}
+
+	/// Asks the user to pick one of `entries`, given as `(id, label)` pairs. Returns the id of the
+	/// chosen entry, or `None` if the user cancelled. Fails if the picker couldn't be started or
+	/// exited with an error.
+	pub fn pick(
+		self,
+		prompt: &str,
+		entries: &[(String, String)],
+	) -> anyhow::Result<Option<String>> {
+		let mut command = Command::new(self.program());
+		match self {
+			Picker::Zenity => command.args(&[
+				"--list",
+				&format!("--text={}", prompt),
+				"--column=id",
+				"--column=Name",
+				"--hide-column=1",
+				"--width=450",
+				"--height=250",
+			]),
+			Picker::Rofi => command.args(&["-dmenu", "-i", "-format", "i", "-p", prompt]),
+		};
+		let mut cmd = match command
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped())
+			.spawn()
+		{
+			Ok(cmd) => cmd,
+			Err(e) if e.kind() == IoErrorKind::NotFound => {
+				return Err(anyhow!("Neither zenity nor rofi is installed"))
+			}
+			Err(e) => return Err(anyhow!("Couldn't start {}: {}", self.program(), e)),
+		};
+		// Write entries to process stdin
+		{
+			let mut stdin = cmd.stdin.as_mut().unwrap();
+			for (id, label) in entries {
+				if self == Picker::Zenity {
+					writeln!(&mut stdin, "{}", id)?;
+				}
+				writeln!(&mut stdin, "{}", label)?;
+			}
+		}
+		// Get process stdout
+		let output = cmd.wait_with_output()?;
+		// Both exit with 1 when the user cancels
+		match output.status.code() {
+			Some(0) => (),
+			Some(1) => return Ok(None),
+			_ => {
+				let stderr = String::from_utf8_lossy(&output.stderr);
+				return Err(anyhow!("{} failed: {}", self.program(), stderr.trim()));
+			}
+		}
+		let choice = String::from_utf8_lossy(&output.stdout);
+		let choice = choice.trim();
+		if choice.is_empty() {
+			return Ok(None);
+		}
+		match self {
+			Picker::Zenity => Ok(Some(choice.to_owned())),
+			Picker::Rofi => {
+				let index: usize = choice.parse()?;
+				let (id, _) =
+					entries.get(index).ok_or_else(|| anyhow!("rofi returned unexpected data!"))?;
+				Ok(Some(id.clone()))
+			}
+		}
+	}
+}
@@ -0,0 +1,56 @@
+//! Reads and controls media players through `playerctl`, as an alternative to talking MPRIS
+//! directly for players that misbehave with it
+
+use std::process::Command;
+
+use anyhow::anyhow;
+
+use crate::{mpris::Song, process};
+
+/// The metadata read for the current song, separated by tabs
+const METADATA_FORMAT: &str =
+	"{{status}}\t{{artist}}\t{{album}}\t{{title}}\t{{xesam:trackNumber}}\t{{mpris:artUrl}}";
+
+/// Runs playerctl with `args`, returning its output
+fn playerctl(args: &[&str]) -> anyhow::Result<String> {
+	let output = process::output(Command::new("playerctl").args(args))
+		.map_err(|e| anyhow!("Couldn't run playerctl: {}", e))?;
+	Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Lists the names of all running players, e.g. `spotify`
+pub fn players() -> anyhow::Result<Vec<String>> {
+	Ok(playerctl(&["--list-all"])?.lines().map(String::from).collect())
+}
+
+/// The current song of `player`, or `None` if playback is stopped
+pub fn song(player: &str) -> anyhow::Result<Option<Song>> {
+	let output = playerctl(&["--player", player, "metadata", "--format", METADATA_FORMAT])?;
+	let mut fields = output.trim_end_matches('\n').split('\t');
+	let playing = match fields.next() {
+		Some("Playing") => true,
+		Some("Paused") => false,
+		_ => return Ok(None),
+	};
+	let mut next = || fields.next().unwrap_or_default().to_owned();
+	let (artist, album, title) = (next(), next(), next());
+	let track = next().parse().ok();
+	let art_url = Some(next()).filter(|url| !url.is_empty());
+	// playerctl doesn't expose the playback rate
+	Ok(Some(Song { artist, album, title, track, playing, rate: 1.0, art_url }))
+}
+
+/// Calls an MPRIS player method on `player`, using the matching playerctl subcommand
+pub fn call(player: &str, method: &str) -> anyhow::Result<()> {
+	let command = match method {
+		"PlayPause" => "play-pause",
+		"Previous" => "previous",
+		"Next" => "next",
+		"Play" => "play",
+		"Pause" => "pause",
+		"Stop" => "stop",
+		_ => return Err(anyhow!("playerctl has no equivalent of {}", method)),
+	};
+	playerctl(&["--player", player, command])?;
+	Ok(())
+}
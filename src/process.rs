@@ -0,0 +1,51 @@
+//! Runs the commands widgets read their data from, limiting how many run at once, so widgets
+//! updating at the same moment don't start a burst of processes. Commands run from clicks, such
+//! as pickers waiting for the user, aren't limited.
+
+use std::{
+	io,
+	process::{Command, Output},
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Condvar, Mutex,
+	},
+};
+
+/// How many commands may run at once
+static LIMIT: AtomicUsize = AtomicUsize::new(4);
+/// How many commands are running
+static RUNNING: Mutex<usize> = Mutex::new(0);
+/// Signaled when a command finishes
+static FINISHED: Condvar = Condvar::new();
+
+/// Sets how many commands may run at once, at least 1. Called before the widgets start.
+pub fn set_limit(limit: usize) {
+	LIMIT.store(limit.max(1), Ordering::Relaxed);
+}
+
+/// Runs `command` and collects its output, waiting first while the limit of commands are running
+pub fn output(command: &mut Command) -> io::Result<Output> {
+	let _slot = Slot::acquire();
+	command.output()
+}
+
+/// One of the commands allowed to run at once, released when dropped
+struct Slot;
+
+impl Slot {
+	fn acquire() -> Self {
+		let mut running = RUNNING.lock().unwrap();
+		while *running >= LIMIT.load(Ordering::Relaxed) {
+			running = FINISHED.wait(running).unwrap();
+		}
+		*running += 1;
+		Slot
+	}
+}
+
+impl Drop for Slot {
+	fn drop(&mut self) {
+		*RUNNING.lock().unwrap() -= 1;
+		FINISHED.notify_one();
+	}
+}
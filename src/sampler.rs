@@ -0,0 +1,153 @@
+//! Reads the system metrics of all system widgets at once, so each metric is read once per tick
+//! and the widgets show readings taken at the same time
+
+use std::{
+	io,
+	path::Path,
+	sync::{Arc, Mutex},
+	thread,
+	time::Duration,
+};
+
+use anyhow::anyhow;
+use systemstat::{BatteryLife, Filesystem, LoadAverage, Memory, Platform, System};
+
+/// How often the metrics are read
+const INTERVAL: Duration = Duration::from_secs(1);
+
+/// A metric read through systemstat
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+	/// The root filesystem
+	Disk,
+	Load,
+	Memory,
+	Temperature,
+	Battery,
+}
+
+/// The value of a metric, or why it couldn't be read. `None` if the metric isn't watched.
+type Reading<T> = Option<Result<T, String>>;
+
+/// The state of the battery
+#[derive(Debug, Clone)]
+pub struct Battery {
+	pub life: BatteryLife,
+	pub on_ac_power: bool,
+}
+
+/// The metrics read in one tick
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+	disk: Reading<Filesystem>,
+	load: Reading<LoadAverage>,
+	memory: Reading<Memory>,
+	temperature: Reading<f32>,
+	battery: Reading<Battery>,
+}
+
+impl Snapshot {
+	/// Reads the `watched` metrics
+	fn take(watched: &[Metric]) -> Self {
+		let system = System::new();
+		Snapshot {
+			disk: read(watched, Metric::Disk, || system.mount_at(Path::new("/"))),
+			load: read(watched, Metric::Load, || system.load_average()),
+			memory: read(watched, Metric::Memory, || system.memory()),
+			temperature: read(watched, Metric::Temperature, || system.cpu_temp()),
+			battery: read(watched, Metric::Battery, || {
+				Ok(Battery { life: system.battery_life()?, on_ac_power: system.on_ac_power()? })
+			}),
+		}
+	}
+
+	/// The root filesystem
+	pub fn disk(&self) -> anyhow::Result<&Filesystem> {
+		value(&self.disk)
+	}
+
+	pub fn load(&self) -> anyhow::Result<&LoadAverage> {
+		value(&self.load)
+	}
+
+	pub fn memory(&self) -> anyhow::Result<&Memory> {
+		value(&self.memory)
+	}
+
+	/// The CPU temperature in degrees Celsius
+	pub fn temperature(&self) -> anyhow::Result<f32> {
+		value(&self.temperature).map(|&temp| temp)
+	}
+
+	pub fn battery(&self) -> anyhow::Result<&Battery> {
+		value(&self.battery)
+	}
+}
+
+/// Reads `metric` with `read` if it's watched
+fn read<T, F: FnOnce() -> io::Result<T>>(
+	watched: &[Metric],
+	metric: Metric,
+	read: F,
+) -> Reading<T> {
+	match watched.contains(&metric) {
+		true => Some(read().map_err(|e| e.to_string())),
+		false => None,
+	}
+}
+
+fn value<T>(reading: &Reading<T>) -> anyhow::Result<&T> {
+	match reading {
+		Some(Ok(value)) => Ok(value),
+		Some(Err(e)) => Err(anyhow!("{}", e)),
+		None => Err(anyhow!("Metric isn't sampled")),
+	}
+}
+
+/// Reads the metrics watched by the widgets every second on a background thread, and shares the
+/// latest snapshot with them
+#[derive(Debug, Clone, Default)]
+pub struct Sampler {
+	watched: Arc<Mutex<Vec<Metric>>>,
+	/// The latest snapshot, once started
+	snapshot: Arc<Mutex<Option<Arc<Snapshot>>>>,
+}
+
+impl Sampler {
+	pub fn new() -> Self {
+		Sampler::default()
+	}
+
+	/// Adds `metric` to the metrics read. Has to be called before the sampler is started.
+	pub fn watch(&self, metric: Metric) {
+		let mut watched = self.watched.lock().unwrap();
+		if !watched.contains(&metric) {
+			watched.push(metric);
+		}
+	}
+
+	/// Takes the first snapshot, and then keeps sampling in the background. Does nothing if no
+	/// metric is watched.
+	pub fn start(&self) {
+		let watched = self.watched.lock().unwrap().clone();
+		if watched.is_empty() {
+			return;
+		}
+		*self.snapshot.lock().unwrap() = Some(Arc::new(Snapshot::take(&watched)));
+		let snapshot = self.snapshot.clone();
+		thread::spawn(move || loop {
+			thread::sleep(INTERVAL);
+			let next = Arc::new(Snapshot::take(&watched));
+			*snapshot.lock().unwrap() = Some(next);
+		});
+	}
+
+	/// The latest snapshot. Until the sampler is started, e.g. while widgets check whether they
+	/// have anything to show, a snapshot is taken on every call.
+	pub fn get(&self) -> Arc<Snapshot> {
+		if let Some(snapshot) = &*self.snapshot.lock().unwrap() {
+			return snapshot.clone();
+		}
+		Arc::new(Snapshot::take(&self.watched.lock().unwrap()))
+	}
+}
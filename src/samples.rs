@@ -0,0 +1,159 @@
+/// A fixed number of the most recent samples of a metric. Once full, each new sample replaces
+/// the oldest one.
+#[derive(Debug, Clone)]
+pub struct SampleBuffer {
+	samples: Vec<f64>,
+	capacity: usize,
+	/// The index of the oldest sample once the buffer is full
+	start: usize,
+}
+
+impl SampleBuffer {
+	/// Creates an empty buffer holding up to `capacity` samples
+	pub fn new(capacity: usize) -> Self {
+		SampleBuffer { samples: Vec::with_capacity(capacity), capacity, start: 0 }
+	}
+
+	/// Adds a sample, dropping the oldest one if the buffer is full
+	pub fn push(&mut self, sample: f64) {
+		if self.samples.len() < self.capacity {
+			self.samples.push(sample);
+		} else if self.capacity > 0 {
+			self.samples[self.start] = sample;
+			self.start = (self.start + 1) % self.capacity;
+		}
+	}
+
+	pub fn len(&self) -> usize {
+		self.samples.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.samples.is_empty()
+	}
+
+	/// The samples, oldest first
+	pub fn iter(&self) -> impl Iterator<Item = f64> + '_ {
+		let (newer, older) = self.samples.split_at(self.start);
+		older.iter().chain(newer).copied()
+	}
+
+	/// The mean of the samples, or `None` if there are none
+	pub fn mean(&self) -> Option<f64> {
+		match self.is_empty() {
+			true => None,
+			false => Some(self.iter().sum::<f64>() / self.len() as f64),
+		}
+	}
+
+	/// The lowest sample, or `None` if there are none
+	pub fn min(&self) -> Option<f64> {
+		self.iter().fold(None, |min, sample| Some(min.map_or(sample, |min: f64| min.min(sample))))
+	}
+
+	/// The highest sample, or `None` if there are none
+	pub fn max(&self) -> Option<f64> {
+		self.iter().fold(None, |max, sample| Some(max.map_or(sample, |max: f64| max.max(sample))))
+	}
+
+	/// The samples scaled from the range `min..=max` to `0.0..=1.0`, oldest first. Samples
+	/// outside the range are clamped, and an empty range maps every sample to 0.0.
+	pub fn normalized(&self, min: f64, max: f64) -> impl Iterator<Item = f64> + '_ {
+		let range = max - min;
+		self.iter().map(move |sample| match range > 0.0 {
+			true => ((sample - min) / range).max(0.0).min(1.0),
+			false => 0.0,
+		})
+	}
+}
+
+/// The mean of the last few readings of a metric, to keep a jittery value from flickering
+#[derive(Debug, Clone)]
+pub struct MovingAverage {
+	samples: SampleBuffer,
+}
+
+impl MovingAverage {
+	/// Averages the last `window` readings. A window of 0 or 1 passes readings through as is.
+	pub fn new(window: usize) -> Self {
+		MovingAverage { samples: SampleBuffer::new(window.max(1)) }
+	}
+
+	/// Adds a reading. Returns the mean of the readings in the window.
+	pub fn add(&mut self, sample: f64) -> f64 {
+		self.samples.push(sample);
+		self.samples.mean().unwrap_or(sample)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn buffer(capacity: usize, samples: &[f64]) -> SampleBuffer {
+		let mut buffer = SampleBuffer::new(capacity);
+		for &sample in samples {
+			buffer.push(sample);
+		}
+		buffer
+	}
+
+	#[test]
+	fn fills_up_to_capacity() {
+		let buffer = buffer(3, &[1.0, 2.0]);
+		assert_eq!(buffer.len(), 2);
+		assert_eq!(buffer.iter().collect::<Vec<_>>(), [1.0, 2.0]);
+	}
+
+	#[test]
+	fn wraps_around_at_capacity() {
+		let mut buffer = buffer(3, &[1.0, 2.0, 3.0]);
+		assert_eq!(buffer.iter().collect::<Vec<_>>(), [1.0, 2.0, 3.0]);
+		buffer.push(4.0);
+		assert_eq!(buffer.len(), 3);
+		assert_eq!(buffer.iter().collect::<Vec<_>>(), [2.0, 3.0, 4.0]);
+		// Around a second time
+		for &sample in &[5.0, 6.0, 7.0] {
+			buffer.push(sample);
+		}
+		assert_eq!(buffer.iter().collect::<Vec<_>>(), [5.0, 6.0, 7.0]);
+		assert_eq!((buffer.min(), buffer.max()), (Some(5.0), Some(7.0)));
+		assert_eq!(buffer.mean(), Some(6.0));
+	}
+
+	#[test]
+	fn zero_capacity() {
+		let buffer = buffer(0, &[1.0, 2.0]);
+		assert!(buffer.is_empty());
+		assert_eq!((buffer.min(), buffer.max(), buffer.mean()), (None, None, None));
+	}
+
+	#[test]
+	fn normalized() {
+		let buffer = buffer(4, &[0.0, 5.0, 10.0, 15.0, 20.0]);
+		assert_eq!(buffer.normalized(0.0, 20.0).collect::<Vec<_>>(), [0.25, 0.5, 0.75, 1.0]);
+		// Clamped to the range
+		assert_eq!(buffer.normalized(10.0, 15.0).collect::<Vec<_>>(), [0.0, 0.0, 1.0, 1.0]);
+		// An empty range
+		assert_eq!(buffer.normalized(5.0, 5.0).collect::<Vec<_>>(), [0.0; 4]);
+		assert_eq!(buffer.normalized(20.0, 0.0).collect::<Vec<_>>(), [0.0; 4]);
+	}
+
+	#[test]
+	fn moving_average() {
+		let mut average = MovingAverage::new(3);
+		let readings = [3.0, 6.0, 9.0, 12.0, 0.0, 6.0];
+		let averages: Vec<_> = readings.iter().map(|&reading| average.add(reading)).collect();
+		// Filling up the window, then averaging the last three readings
+		assert_eq!(averages, [3.0, 4.5, 6.0, 9.0, 7.0, 6.0]);
+	}
+
+	#[test]
+	fn moving_average_passes_through() {
+		for &window in &[0, 1] {
+			let mut average = MovingAverage::new(window);
+			assert_eq!(average.add(3.0), 3.0);
+			assert_eq!(average.add(7.5), 7.5);
+		}
+	}
+}
@@ -0,0 +1,176 @@
+//! Probes every data source the widgets read from once, so users can tell which dependency is
+//! missing on a new machine without reading the bar
+
+use std::{io::ErrorKind as IoErrorKind, process::Command};
+
+use anyhow::anyhow;
+use unixbar::widget::backlight::Backlight;
+
+use crate::{
+	config::{Config, VolumeBackend},
+	hardware::Hardware,
+	sampler::{Metric, Sampler, Snapshot},
+	widgets::keyboard,
+};
+
+/// The outcome of probing a data source
+#[derive(Debug)]
+enum Status {
+	Ok,
+	/// Not probed, for the given reason
+	Skipped(String),
+	Failed(anyhow::Error),
+}
+
+/// A data source the widgets read from
+struct Source {
+	name: &'static str,
+	/// Whether the configured widgets read from it. Failures of sources they don't need are
+	/// shown, but don't fail the test.
+	required: fn(&Config) -> bool,
+	probe: fn(Hardware, &Snapshot) -> Status,
+}
+
+const SOURCES: &[Source] = &[
+	Source {
+		name: "ALSA mixer",
+		required: |config| {
+			uses(config, &["volume"]) && config.volume.backend == VolumeBackend::Alsa
+		},
+		probe: |_, _| volume(VolumeBackend::Alsa),
+	},
+	Source {
+		name: "PulseAudio",
+		required: |config| {
+			uses(config, &["output", "microphone"])
+				|| uses(config, &["volume"]) && config.volume.backend == VolumeBackend::Pulse
+		},
+		probe: |_, _| volume(VolumeBackend::Pulse),
+	},
+	Source {
+		name: "disk",
+		required: |config| uses(config, &["disk"]),
+		probe: |_, snapshot| status(snapshot.disk().map(drop)),
+	},
+	Source {
+		name: "load",
+		required: |config| uses(config, &["load"]),
+		probe: |_, snapshot| status(snapshot.load().map(drop)),
+	},
+	Source {
+		name: "memory",
+		required: |config| uses(config, &["memory", "sparkline"]),
+		probe: |_, snapshot| status(snapshot.memory().map(drop)),
+	},
+	Source {
+		name: "temperature",
+		required: |config| uses(config, &["temperature"]),
+		probe: |_, snapshot| status(snapshot.temperature().map(drop)),
+	},
+	Source {
+		name: "battery",
+		required: |config| uses(config, &["battery"]),
+		probe: |hardware, snapshot| match hardware.battery {
+			true => status(snapshot.battery().map(drop)),
+			false => Status::Skipped(String::from("no battery found")),
+		},
+	},
+	Source {
+		name: "nmcli",
+		required: |config| uses(config, &["network"]),
+		probe: |_, _| status(command("nmcli", &["networking", "connectivity", "check"]).map(drop)),
+	},
+	Source {
+		name: "ibus",
+		required: |config| uses(config, &["keyboard"]),
+		probe: |_, _| {
+			let output = command("ibus", &["engine"]);
+			status(output.and_then(|output| match keyboard::layout(&output) {
+				Some(_) => Ok(()),
+				None => Err(anyhow!("ibus reported no engine")),
+			}))
+		},
+	},
+	Source {
+		name: "backlight",
+		required: |config| uses(config, &["brightness"]),
+		probe: |hardware, _| match (hardware.backlight, Backlight::get()) {
+			(true, Ok(_)) => Status::Ok,
+			(true, Err(e)) if e.kind() != IoErrorKind::NotFound => Status::Failed(e.into()),
+			_ => Status::Skipped(String::from("no backlight found")),
+		},
+	},
+];
+
+/// Whether any of `widgets` is configured
+fn uses(config: &Config, widgets: &[&str]) -> bool {
+	config.widgets.iter().any(|widget| widgets.contains(&widget.as_str()))
+}
+
+fn status(result: anyhow::Result<()>) -> Status {
+	match result {
+		Ok(()) => Status::Ok,
+		Err(e) => Status::Failed(e),
+	}
+}
+
+/// Probes the volume `backend`, if it's built in
+#[cfg(any(feature = "alsa", feature = "pulse"))]
+fn volume(backend: VolumeBackend) -> Status {
+	let built = match backend {
+		VolumeBackend::Alsa => cfg!(feature = "alsa"),
+		VolumeBackend::Pulse => cfg!(feature = "pulse"),
+	};
+	match built {
+		true => status(backend.probe()),
+		false => Status::Skipped(String::from("disabled in this build")),
+	}
+}
+
+#[cfg(not(any(feature = "alsa", feature = "pulse")))]
+fn volume(_: VolumeBackend) -> Status {
+	Status::Skipped(String::from("disabled in this build"))
+}
+
+/// Runs `program` with `args`, returning its output if it succeeded
+fn command(program: &str, args: &[&str]) -> anyhow::Result<String> {
+	let output = Command::new(program)
+		.args(args)
+		.output()
+		.map_err(|e| anyhow!("Couldn't run {}: {}", program, e))?;
+	let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+	match output.status.success() {
+		true => Ok(stdout),
+		false => {
+			Err(anyhow!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim()))
+		}
+	}
+}
+
+/// Probes every data source and prints how it went. Returns whether all sources the configured
+/// widgets need work.
+pub fn run(config: &Config) -> bool {
+	let hardware = Hardware::detect();
+	let sampler = Sampler::new();
+	for &metric in
+		&[Metric::Disk, Metric::Load, Metric::Memory, Metric::Temperature, Metric::Battery]
+	{
+		sampler.watch(metric);
+	}
+	// Not started, so this takes a snapshot now
+	let snapshot = sampler.get();
+	let mut passed = true;
+	for source in SOURCES {
+		let required = (source.required)(config);
+		match (source.probe)(hardware, &snapshot) {
+			Status::Ok => println!("{:<12} OK", source.name),
+			Status::Skipped(reason) => println!("{:<12} skipped: {}", source.name, reason),
+			Status::Failed(e) if required => {
+				passed = false;
+				println!("{:<12} error: {}", source.name, e);
+			}
+			Status::Failed(e) => println!("{:<12} error (unused): {}", source.name, e),
+		}
+	}
+	passed
+}
@@ -0,0 +1,108 @@
+//! Sunrise, sunset and moon phase calculations. These only need the coordinates and the date,
+//! so they work offline.
+
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+
+use crate::icons::{self, Icon};
+
+/// The Julian day of 2000-01-01 12:00, the J2000 epoch
+const J2000: f64 = 2_451_545.0;
+/// The Julian day of the Unix epoch
+const UNIX_EPOCH: f64 = 2_440_587.5;
+/// The length of a lunar cycle in days
+const SYNODIC_MONTH: f64 = 29.530_588_853;
+/// A new moon to count lunar cycles from, 2000-01-06 18:14 UTC
+const NEW_MOON: i64 = 947_182_440;
+
+/// The sunrise and sunset on `date` at the given latitude and longitude in degrees, with east
+/// and north positive. Returns `None` during polar day or night, when the sun doesn't rise or
+/// set.
+pub fn sunrise_sunset(
+	date: NaiveDate,
+	latitude: f64,
+	longitude: f64,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+	// Following the sunrise equation, with angles in degrees
+	let (sin, cos) = (|deg: f64| deg.to_radians().sin(), |deg: f64| deg.to_radians().cos());
+	let days = (date - NaiveDate::from_ymd_opt(2000, 1, 1)?).num_days() as f64;
+	let mean_solar_time = days + 0.0008 - longitude / 360.0;
+	let anomaly = (357.5291 + 0.985_600_28 * mean_solar_time).rem_euclid(360.0);
+	let center = 1.9148 * sin(anomaly) + 0.02 * sin(2.0 * anomaly) + 0.0003 * sin(3.0 * anomaly);
+	let ecliptic_longitude = (anomaly + center + 180.0 + 102.9372).rem_euclid(360.0);
+	let transit =
+		J2000 + mean_solar_time + 0.0053 * sin(anomaly) - 0.0069 * sin(2.0 * ecliptic_longitude);
+	let declination = (sin(ecliptic_longitude) * sin(23.4397)).asin().to_degrees();
+	// The sun's center is 0.833° below the horizon at sunrise, due to refraction and its size
+	let cos_hour_angle =
+		(sin(-0.833) - sin(latitude) * sin(declination)) / (cos(latitude) * cos(declination));
+	if !(-1.0..=1.0).contains(&cos_hour_angle) {
+		return None;
+	}
+	let hour_angle = cos_hour_angle.acos().to_degrees();
+	let time =
+		|julian: f64| Utc.timestamp_opt(((julian - UNIX_EPOCH) * 86400.0) as i64, 0).single();
+	Some((time(transit - hour_angle / 360.0)?, time(transit + hour_angle / 360.0)?))
+}
+
+/// How far the moon is through its cycle at `time`, from 0 at new moon through 0.5 at full moon
+pub fn moon_phase(time: DateTime<Utc>) -> f64 {
+	let days = (time.timestamp() - NEW_MOON) as f64 / 86400.0;
+	(days / SYNODIC_MONTH).rem_euclid(1.0)
+}
+
+/// The moon icon for a phase returned by `moon_phase`
+pub fn moon_icon(phase: f64) -> Icon {
+	icons::MOON[((phase * 8.0).round() as usize) % icons::MOON.len()]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// How far the results may be from almanac times
+	const TOLERANCE_SECS: i64 = 180;
+
+	fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+		NaiveDate::from_ymd_opt(year, month, day).unwrap()
+	}
+
+	fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+		Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+	}
+
+	fn assert_near(actual: DateTime<Utc>, expected: DateTime<Utc>) {
+		let off = (actual - expected).num_seconds().abs();
+		assert!(off <= TOLERANCE_SECS, "{} is {}s off {}", actual, off, expected);
+	}
+
+	#[test]
+	fn london_summer_solstice() {
+		// 04:43 and 21:21 BST
+		let (rise, set) = sunrise_sunset(date(2021, 6, 21), 51.5074, -0.1278).unwrap();
+		assert_near(rise, utc(2021, 6, 21, 3, 43));
+		assert_near(set, utc(2021, 6, 21, 20, 21));
+	}
+
+	#[test]
+	fn new_york_winter_solstice() {
+		// 07:16 and 16:32 EST
+		let (rise, set) = sunrise_sunset(date(2021, 12, 21), 40.7128, -74.0060).unwrap();
+		assert_near(rise, utc(2021, 12, 21, 12, 16));
+		assert_near(set, utc(2021, 12, 21, 21, 32));
+	}
+
+	#[test]
+	fn polar_day_and_night() {
+		// Tromsø has midnight sun in June and polar night in December
+		assert_eq!(sunrise_sunset(date(2021, 6, 21), 69.6492, 18.9553), None);
+		assert_eq!(sunrise_sunset(date(2021, 12, 21), 69.6492, 18.9553), None);
+	}
+
+	#[test]
+	fn full_moon() {
+		// 2021-01-28 19:16 UTC
+		let phase = moon_phase(utc(2021, 1, 28, 19, 16));
+		assert!((phase - 0.5).abs() < 0.02, "phase {}", phase);
+		assert_eq!(moon_icon(phase), icons::MOON[4]);
+	}
+}
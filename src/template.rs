@@ -0,0 +1,70 @@
+//! Format strings with named placeholders, used to make widget layouts configurable
+
+/// Fills in the `{name}` placeholders of `template` with the values returned by `lookup`.
+/// Placeholders without a value are left empty. `{{` and `}}` stand for literal braces.
+pub fn render<F: Fn(&str) -> Option<String>>(template: &str, lookup: F) -> String {
+	let mut output = String::with_capacity(template.len());
+	let mut chars = template.chars().peekable();
+	while let Some(c) = chars.next() {
+		match c {
+			'{' if chars.peek() == Some(&'{') => {
+				chars.next();
+				output.push('{');
+			}
+			'}' if chars.peek() == Some(&'}') => {
+				chars.next();
+				output.push('}');
+			}
+			'{' => {
+				let name: String = chars.by_ref().take_while(|&c| c != '}').collect();
+				output.push_str(&lookup(&name).unwrap_or_default());
+			}
+			c => output.push(c),
+		}
+	}
+	output
+}
+
+/// Fills in the placeholders of `template` from a list of names and values
+pub fn fill(template: &str, values: &[(&str, &dyn ToString)]) -> String {
+	render(template, |name| {
+		values.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string())
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn substitutes_placeholders() {
+		let values: &[(&str, &dyn ToString)] = &[("icon", &"*"), ("value", &42), ("unit", &"GiB")];
+		assert_eq!(fill("{icon} {value} {unit}", values), "* 42 GiB");
+		assert_eq!(fill("{value}{value}", values), "4242");
+		assert_eq!(fill("no placeholders", values), "no placeholders");
+	}
+
+	#[test]
+	fn unknown_placeholders_are_empty() {
+		let values: &[(&str, &dyn ToString)] = &[("value", &42)];
+		assert_eq!(fill("{value} {unknown}!", values), "42 !");
+		assert_eq!(fill("{}", values), "");
+		// An unclosed placeholder runs to the end of the template
+		assert_eq!(fill("{value} {value", values), "42 42");
+		assert_eq!(fill("{value} {val", values), "42 ");
+	}
+
+	#[test]
+	fn escaped_braces() {
+		let values: &[(&str, &dyn ToString)] = &[("value", &42)];
+		assert_eq!(fill("{{value}}", values), "{value}");
+		assert_eq!(fill("{{{value}}}", values), "{42}");
+		assert_eq!(fill("}} {{", values), "} {");
+	}
+
+	#[test]
+	fn render_looks_up_each_placeholder() {
+		let rendered = render("{a}-{b}", |name| Some(name.to_uppercase()));
+		assert_eq!(rendered, "A-B");
+	}
+}
@@ -0,0 +1,158 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{config::Urgency, notify::Notification};
+
+/// Which side of the threshold a value has to reach to trigger the notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+	Above,
+	Below,
+}
+
+/// The notification sent when a threshold is crossed
+#[derive(Debug, Clone)]
+pub struct Alert {
+	pub summary: String,
+	pub body: String,
+	pub icon: String,
+	pub urgency: Urgency,
+	/// Whether to attach the value as a `value` hint, which some notification daemons draw as a
+	/// gauge
+	pub value_hint: bool,
+}
+
+/// Notifies once when a value crosses a threshold. The notification is sent again only after
+/// the value has moved back past the threshold by more than the hysteresis, so a value
+/// hovering around the threshold doesn't cause a notification on every reading.
+pub struct ThresholdNotifier {
+	direction: Direction,
+	threshold: f64,
+	hysteresis: f64,
+	alert: Alert,
+	/// Whether the notification has been sent since the value was last back in range
+	triggered: AtomicBool,
+}
+
+impl ThresholdNotifier {
+	pub fn new(direction: Direction, threshold: f64, hysteresis: f64, alert: Alert) -> Self {
+		ThresholdNotifier {
+			direction,
+			threshold,
+			hysteresis,
+			alert,
+			triggered: AtomicBool::new(false),
+		}
+	}
+
+	/// Updates the state with a new reading. Returns whether the threshold was just crossed.
+	pub fn update(&self, value: f64) -> bool {
+		let (crossed, recovered) = match self.direction {
+			Direction::Above => (value >= self.threshold, value < self.threshold - self.hysteresis),
+			Direction::Below => (value <= self.threshold, value > self.threshold + self.hysteresis),
+		};
+		self.latch(crossed, recovered)
+	}
+
+	/// Updates the state for sources that decide themselves whether the threshold has been
+	/// `crossed`, or the value has `recovered`. Returns whether the threshold was just crossed.
+	pub fn latch(&self, crossed: bool, recovered: bool) -> bool {
+		if recovered {
+			self.reset();
+			false
+		} else {
+			crossed && !self.triggered.swap(true, Ordering::AcqRel)
+		}
+	}
+
+	/// Allows the notification to be sent again, e.g. once a charger is connected
+	pub fn reset(&self) {
+		self.triggered.store(false, Ordering::Release);
+	}
+
+	/// Updates the state with a new reading, and sends the notification if the threshold was
+	/// just crossed. Returns whether it was sent.
+	pub fn check(&self, value: f64) -> anyhow::Result<bool> {
+		if !self.update(value) {
+			return Ok(false);
+		}
+		self.notify(value)?;
+		Ok(true)
+	}
+
+	/// Sends the notification for `value`
+	pub fn notify(&self, value: f64) -> anyhow::Result<()> {
+		let mut notif = Notification::new(
+			&self.alert.summary,
+			Some(self.alert.body.as_str()),
+			Some(self.alert.icon.as_str()),
+		)
+		.urgency(self.alert.urgency);
+		if self.alert.value_hint {
+			notif = notif.value(value.round() as i32);
+		}
+		notif.show()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn notifier(direction: Direction) -> ThresholdNotifier {
+		let alert = Alert {
+			summary: String::from("summary"),
+			body: String::from("body"),
+			icon: String::from("icon"),
+			urgency: Urgency::Normal,
+			value_hint: false,
+		};
+		ThresholdNotifier::new(direction, 90.0, 5.0, alert)
+	}
+
+	#[test]
+	fn fires_once_when_crossed() {
+		let notifier = notifier(Direction::Above);
+		assert!(!notifier.update(80.0));
+		assert!(notifier.update(90.0));
+		// Staying past the threshold doesn't fire again
+		assert!(!notifier.update(95.0));
+		assert!(!notifier.update(90.0));
+	}
+
+	#[test]
+	fn rearms_past_hysteresis() {
+		let notifier = notifier(Direction::Above);
+		assert!(notifier.update(91.0));
+		// Back below the threshold, but within the margin
+		assert!(!notifier.update(86.0));
+		assert!(!notifier.update(91.0));
+		// Exactly at the margin isn't enough either
+		assert!(!notifier.update(85.0));
+		assert!(!notifier.update(91.0));
+		assert!(!notifier.update(84.0));
+		assert!(notifier.update(91.0));
+	}
+
+	#[test]
+	fn below() {
+		let notifier = notifier(Direction::Below);
+		assert!(!notifier.update(95.0));
+		assert!(notifier.update(90.0));
+		assert!(!notifier.update(80.0));
+		assert!(!notifier.update(95.0));
+		assert!(!notifier.update(90.0));
+		assert!(!notifier.update(96.0));
+		assert!(notifier.update(89.0));
+	}
+
+	#[test]
+	fn latch_and_reset() {
+		let notifier = notifier(Direction::Below);
+		assert!(notifier.latch(true, false));
+		assert!(!notifier.latch(true, false));
+		assert!(!notifier.latch(false, true));
+		assert!(notifier.latch(true, false));
+		notifier.reset();
+		assert!(notifier.latch(true, false));
+	}
+}
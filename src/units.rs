@@ -0,0 +1,124 @@
+use crate::config::{ByteUnit, TemperatureUnit};
+
+/// The decimal unit prefixes, each 1000 times the previous one, named like systemstat does
+const DECIMAL: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+/// The binary unit prefixes, each 1024 times the previous one
+const BINARY: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+/// Formats a byte count using binary unit prefixes, e.g. `1.5 MiB`
+pub fn bytes(bytes: f64) -> String {
+	let (value, unit) = scaled(bytes, ByteUnit::Binary, 1);
+	format!("{} {}", value, unit)
+}
+
+/// Formats a byte count in `unit` with `precision` decimals. Returns the value and the unit
+/// separately. In the automatic units, the value is rounded before the unit is picked, so e.g.
+/// 1023.99 MiB is shown as 1.0 GiB rather than 1024.0 MiB. Whole bytes are shown without
+/// decimals.
+pub fn scaled(bytes: f64, unit: ByteUnit, precision: usize) -> (String, &'static str) {
+	let (base, units, fixed) = match unit {
+		ByteUnit::Auto => (1000.0, &DECIMAL, None),
+		ByteUnit::Binary => (1024.0, &BINARY, None),
+		ByteUnit::B => (1000.0, &DECIMAL, Some(0)),
+		ByteUnit::KB => (1000.0, &DECIMAL, Some(1)),
+		ByteUnit::MB => (1000.0, &DECIMAL, Some(2)),
+		ByteUnit::GB => (1000.0, &DECIMAL, Some(3)),
+		ByteUnit::TB => (1000.0, &DECIMAL, Some(4)),
+		ByteUnit::KiB => (1024.0, &BINARY, Some(1)),
+		ByteUnit::MiB => (1024.0, &BINARY, Some(2)),
+		ByteUnit::GiB => (1024.0, &BINARY, Some(3)),
+		ByteUnit::TiB => (1024.0, &BINARY, Some(4)),
+	};
+	let scale = 10f64.powi(precision as i32);
+	let mut index = fixed.unwrap_or(0);
+	let mut value = bytes / f64::powi(base, index as i32);
+	while fixed.is_none() && index < units.len() - 1 && (value * scale).round() / scale >= base {
+		value /= base;
+		index += 1;
+	}
+	let precision = if index == 0 { 0 } else { precision };
+	(format!("{:.*}", precision, value), units[index])
+}
+
+impl TemperatureUnit {
+	/// Converts a temperature in degrees Celsius to this unit
+	pub fn from_celsius(self, celsius: f32) -> f32 {
+		match self {
+			TemperatureUnit::Celsius => celsius,
+			TemperatureUnit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+		}
+	}
+
+	/// The other unit, which a click on the temperature widget switches to
+	pub fn toggled(self) -> Self {
+		match self {
+			TemperatureUnit::Celsius => TemperatureUnit::Fahrenheit,
+			TemperatureUnit::Fahrenheit => TemperatureUnit::Celsius,
+		}
+	}
+
+	pub fn symbol(self) -> &'static str {
+		match self {
+			TemperatureUnit::Celsius => "°C",
+			TemperatureUnit::Fahrenheit => "°F",
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const MIB: f64 = 1024.0 * 1024.0;
+	const GIB: f64 = 1024.0 * MIB;
+
+	fn shown(bytes: f64, unit: ByteUnit, precision: usize) -> String {
+		let (value, unit) = scaled(bytes, unit, precision);
+		format!("{} {}", value, unit)
+	}
+
+	#[test]
+	fn binary_gib_boundary() {
+		assert_eq!(shown(1023.94 * MIB, ByteUnit::Binary, 1), "1023.9 MiB");
+		// Rounds up to 1024.0 MiB, so it's shown as GiB
+		assert_eq!(shown(1023.95 * MIB, ByteUnit::Binary, 1), "1.0 GiB");
+		assert_eq!(shown(GIB, ByteUnit::Binary, 1), "1.0 GiB");
+		assert_eq!(shown(1023.4 * MIB, ByteUnit::Binary, 0), "1023 MiB");
+		assert_eq!(shown(1023.5 * MIB, ByteUnit::Binary, 0), "1 GiB");
+		assert_eq!(shown(1023.994 * MIB, ByteUnit::Binary, 2), "1023.99 MiB");
+		assert_eq!(shown(1023.995 * MIB, ByteUnit::Binary, 2), "1.00 GiB");
+	}
+
+	#[test]
+	fn decimal_by_default() {
+		// Like systemstat shows sizes
+		assert_eq!(shown(999.94e6, ByteUnit::Auto, 1), "999.9 MB");
+		assert_eq!(shown(999.95e6, ByteUnit::Auto, 1), "1.0 GB");
+		assert_eq!(shown(123.456e9, ByteUnit::Auto, 1), "123.5 GB");
+		assert_eq!(shown(512.0, ByteUnit::Auto, 1), "512 B");
+	}
+
+	#[test]
+	fn fixed_units() {
+		assert_eq!(shown(0.5 * GIB, ByteUnit::GiB, 1), "0.5 GiB");
+		assert_eq!(shown(1023.95 * MIB, ByteUnit::MiB, 1), "1024.0 MiB");
+		assert_eq!(shown(2048.0 * GIB, ByteUnit::GiB, 0), "2048 GiB");
+		assert_eq!(shown(1.5e9, ByteUnit::MB, 0), "1500 MB");
+	}
+
+	#[test]
+	fn celsius_to_fahrenheit() {
+		let cases = [(-40.0, -40.0), (0.0, 32.0), (37.0, 98.6), (100.0, 212.0)];
+		for &(celsius, fahrenheit) in &cases {
+			let converted = TemperatureUnit::Fahrenheit.from_celsius(celsius);
+			assert!((converted - fahrenheit).abs() < 0.01, "{} °C is {} °F", celsius, converted);
+			assert_eq!(TemperatureUnit::Celsius.from_celsius(celsius), celsius);
+		}
+	}
+
+	#[test]
+	fn toggled_unit() {
+		assert_eq!(TemperatureUnit::Celsius.toggled(), TemperatureUnit::Fahrenheit);
+		assert_eq!(TemperatureUnit::Fahrenheit.toggled().symbol(), "°C");
+	}
+}
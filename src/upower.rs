@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use dbus::blocking::{stdintf::org_freedesktop_dbus::Properties, Connection};
+
+const BUS_NAME: &str = "org.freedesktop.UPower";
+const DISPLAY_DEVICE: &str = "/org/freedesktop/UPower/devices/DisplayDevice";
+const DEVICE_IFACE: &str = "org.freedesktop.UPower.Device";
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+/// UPower's `State` values meaning a power source is connected
+const CHARGING: u32 = 1;
+const FULLY_CHARGED: u32 = 4;
+const PENDING_CHARGE: u32 = 5;
+/// UPower's `WarningLevel` values meaning the battery is low or critical
+const WARNING_LOW: u32 = 3;
+
+/// The combined state of all batteries, as reported by UPower
+pub struct Device {
+	/// Remaining capacity in percent
+	pub percentage: f64,
+	/// Whether a power source is connected
+	pub charging: bool,
+	/// Estimated time until the battery is empty or full, if known
+	pub time_left: Option<Duration>,
+	/// Whether UPower considers the battery low
+	pub warning: bool,
+}
+
+impl Device {
+	/// Reads UPower's display device. Returns `None` if there is no battery.
+	pub fn display() -> anyhow::Result<Option<Device>> {
+		let conn = Connection::new_system()?;
+		let proxy = conn.with_proxy(BUS_NAME, DISPLAY_DEVICE, TIMEOUT);
+		let present: bool = proxy.get(DEVICE_IFACE, "IsPresent")?;
+		if !present {
+			return Ok(None);
+		}
+		let percentage: f64 = proxy.get(DEVICE_IFACE, "Percentage")?;
+		let state: u32 = proxy.get(DEVICE_IFACE, "State")?;
+		let charging = [CHARGING, FULLY_CHARGED, PENDING_CHARGE].contains(&state);
+		let time: i64 =
+			proxy.get(DEVICE_IFACE, if charging { "TimeToFull" } else { "TimeToEmpty" })?;
+		let warning_level: u32 = proxy.get(DEVICE_IFACE, "WarningLevel")?;
+		Ok(Some(Device {
+			percentage,
+			charging,
+			// UPower reports 0 while it's still estimating
+			time_left: Some(time)
+				.filter(|&time| time > 0)
+				.map(|time| Duration::from_secs(time as u64)),
+			warning: warning_level >= WARNING_LOW,
+		}))
+	}
+}
@@ -0,0 +1,87 @@
+//! The version of agrabar and of the system libraries it's linked against, for bug reports. The
+//! libraries are asked for their version at runtime, since it may differ from the one built
+//! against.
+
+#[cfg(any(feature = "alsa", feature = "pulse", feature = "notify"))]
+use std::{ffi::CStr, os::raw::c_char};
+
+#[cfg(feature = "alsa")]
+extern "C" {
+	fn snd_asoundlib_version() -> *const c_char;
+}
+
+#[cfg(feature = "pulse")]
+extern "C" {
+	fn pa_get_library_version() -> *const c_char;
+}
+
+#[cfg(feature = "notify")]
+extern "C" {
+	fn notify_get_server_info(
+		name: *mut *mut c_char,
+		vendor: *mut *mut c_char,
+		version: *mut *mut c_char,
+		spec_version: *mut *mut c_char,
+	) -> i32;
+}
+
+/// Reads a string returned by a C library, if it returned one
+#[cfg(any(feature = "alsa", feature = "pulse", feature = "notify"))]
+fn string(ptr: *const c_char) -> Option<String> {
+	match ptr.is_null() {
+		true => None,
+		// SAFETY: the libraries return null or a nul-terminated string
+		false => Some(unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned()),
+	}
+}
+
+/// The version of the ALSA library
+#[cfg(feature = "alsa")]
+fn alsa() -> String {
+	// SAFETY: returns a static string
+	string(unsafe { snd_asoundlib_version() }).unwrap_or_else(|| String::from("unknown"))
+}
+
+/// The version of the PulseAudio client library
+#[cfg(feature = "pulse")]
+fn pulse() -> String {
+	// SAFETY: returns a static string
+	string(unsafe { pa_get_library_version() }).unwrap_or_else(|| String::from("unknown"))
+}
+
+/// The notification server libnotify talks to. libnotify doesn't report its own version, and has
+/// to be initialized first.
+#[cfg(feature = "notify")]
+fn notify() -> String {
+	if let Err(e) = crate::notify::init() {
+		return format!("couldn't initialize ({})", e);
+	}
+	let mut fields = [std::ptr::null_mut(); 4];
+	let [name, vendor, version, spec] = &mut fields;
+	// SAFETY: the pointers are only written to, with strings owned by the caller. They're
+	// leaked, this only runs once.
+	let found = unsafe { notify_get_server_info(name, vendor, version, spec) } != 0;
+	let [name, vendor, version, spec] = fields.map(|field| string(field).unwrap_or_default());
+	match found {
+		true => format!("server {} {} by {}, spec {}", name, version, vendor, spec),
+		false => String::from("no notification server running"),
+	}
+}
+
+/// The versions of agrabar and its libraries, one per line
+pub fn report() -> String {
+	let mut lines = vec![format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))];
+	#[cfg(feature = "alsa")]
+	lines.push(format!("alsa: {}", alsa()));
+	#[cfg(not(feature = "alsa"))]
+	lines.push(String::from("alsa: not built in"));
+	#[cfg(feature = "pulse")]
+	lines.push(format!("pulse: {}", pulse()));
+	#[cfg(not(feature = "pulse"))]
+	lines.push(String::from("pulse: not built in"));
+	#[cfg(feature = "notify")]
+	lines.push(format!("libnotify: {}", notify()));
+	#[cfg(not(feature = "notify"))]
+	lines.push(String::from("libnotify: not built in, notifications are logged"));
+	lines.join("\n")
+}
@@ -1,33 +1,189 @@
-use std::{
-	io::Write,
-	process::{Command, Stdio},
-};
+#[cfg(feature = "pulse")]
+use std::{collections::HashMap, process::Command};
+#[cfg(feature = "alsa")]
+use std::{thread, time::Duration};
 
+#[cfg(feature = "alsa")]
 use alsa::mixer::{Mixer, SelemChannelId, SelemId};
 use anyhow::anyhow;
-use libnotify::Notification;
-use pulsectl::controllers::{AppControl, DeviceControl, SinkController};
+#[cfg(feature = "pulse")]
+use pulsectl::controllers::{
+	types::{ApplicationInfo, DeviceInfo},
+	AppControl, DeviceControl, SinkController, SourceController,
+};
+#[cfg(feature = "pulse")]
+use regex::Regex;
+
+#[cfg(feature = "pulse")]
+use crate::{config::DeviceMenuConfig, notify::Notification, picker::Picker};
+use crate::{
+	config::VolumeBackend,
+	icons::{self, Icon},
+};
+
+/// PulseAudio's volume for 100%
+#[cfg(feature = "pulse")]
+const PA_VOLUME_NORM: f64 = 65536.0;
+/// Marks the picker entries switching a card profile rather than the device
+#[cfg(feature = "pulse")]
+const PROFILE_ENTRY: &str = "card-profile:";
+/// How many times to try opening the ALSA mixer
+#[cfg(feature = "alsa")]
+const MIXER_ATTEMPTS: u32 = 4;
+
+impl VolumeBackend {
+	/// Changes the volume by `diff` percent. Returns the new volume in percent.
+	pub fn add(self, diff: i8) -> anyhow::Result<u8> {
+		match self {
+			#[cfg(feature = "alsa")]
+			VolumeBackend::Alsa => add(diff),
+			#[cfg(feature = "pulse")]
+			VolumeBackend::Pulse => {
+				let mut controller = SinkController::create()?;
+				let index = controller.get_default_device()?.index;
+				let delta = f64::from(diff.abs()) / 100.0;
+				if diff < 0 {
+					controller.decrease_device_volume_by_percent(index, delta);
+				} else {
+					controller.increase_device_volume_by_percent(index, delta);
+				}
+				Ok((pulse_state()?.0 * 100.0).round() as u8)
+			}
+			#[allow(unreachable_patterns)]
+			_ => Err(self.unsupported()),
+		}
+	}
+
+	/// Toggles whether the volume is muted
+	pub fn mute(self) -> anyhow::Result<()> {
+		match self {
+			#[cfg(feature = "alsa")]
+			VolumeBackend::Alsa => Ok(mute()?),
+			#[cfg(feature = "pulse")]
+			VolumeBackend::Pulse => {
+				let mut controller = SinkController::create()?;
+				let device = controller.get_default_device()?;
+				controller.set_device_mute_by_index(device.index, !device.mute);
+				Ok(())
+			}
+			#[allow(unreachable_patterns)]
+			_ => Err(self.unsupported()),
+		}
+	}
+
+	/// The volume in decibels for a volume where 1.0 is 100%, or `None` when silent or unknown
+	pub fn db(self, volume: f32) -> Option<f32> {
+		match self {
+			#[cfg(feature = "alsa")]
+			VolumeBackend::Alsa => db().ok().flatten(),
+			VolumeBackend::Pulse => pulse_db(volume),
+			#[allow(unreachable_patterns)]
+			_ => None,
+		}
+	}
+
+	/// Checks that the sound system is running and has a device to control, so the widgets can
+	/// be hidden where there is no sound card
+	pub fn probe(self) -> anyhow::Result<()> {
+		match self {
+			#[cfg(feature = "alsa")]
+			VolumeBackend::Alsa => {
+				let mixer = Mixer::new("default", false)?;
+				let found = mixer.find_selem(&SelemId::new("Master", 0)).is_some();
+				match found {
+					true => Ok(()),
+					false => Err(anyhow!("The ALSA mixer has no Master control")),
+				}
+			}
+			#[cfg(feature = "pulse")]
+			VolumeBackend::Pulse => {
+				SinkController::create()?.get_default_device()?;
+				Ok(())
+			}
+			#[allow(unreachable_patterns)]
+			_ => Err(self.unsupported()),
+		}
+	}
 
-pub fn add(diff: i8) -> anyhow::Result<()> {
-	let mixer = Mixer::new("default", false)?;
+	/// The error for a backend disabled at build time
+	pub fn unsupported(self) -> anyhow::Error {
+		let feature = match self {
+			VolumeBackend::Alsa => "alsa",
+			VolumeBackend::Pulse => "pulse",
+		};
+		anyhow!("The {} volume backend is disabled in this build", feature)
+	}
+}
+
+/// The volume of the default PulseAudio sink, where 1.0 is 100%, and whether it's muted
+#[cfg(feature = "pulse")]
+pub fn pulse_state() -> anyhow::Result<(f32, bool)> {
+	let device = SinkController::create()?.get_default_device()?;
+	let volume = f64::from(device.volume.avg().0) / PA_VOLUME_NORM;
+	Ok((volume as f32, device.mute))
+}
+
+/// Opens the default ALSA mixer. The device can be busy for a moment while audio devices are
+/// switched, so opening it is retried a few times with increasing delays.
+#[cfg(feature = "alsa")]
+fn open_mixer() -> anyhow::Result<Mixer> {
+	let mut delay = Duration::from_millis(50);
+	for _ in 1..MIXER_ATTEMPTS {
+		match Mixer::new("default", false) {
+			Ok(mixer) => return Ok(mixer),
+			Err(_) => thread::sleep(delay),
+		}
+		delay *= 2;
+	}
+	Mixer::new("default", false).map_err(|e| anyhow!("Couldn't open the ALSA mixer: {}", e))
+}
+
+/// The ALSA volume in decibels, or `None` at the minimum volume, which is silent
+#[cfg(feature = "alsa")]
+pub fn db() -> anyhow::Result<Option<f32>> {
+	let mixer = open_mixer()?;
+	let se_id = SelemId::new("Master", 0);
+	let selem = mixer.find_selem(&se_id).ok_or_else(|| anyhow!("Could not find alsa selem"))?;
+	let db = selem.get_playback_vol_db(SelemChannelId::FrontLeft)?.to_db();
+	// ALSA reports silence as a very large negative gain
+	Ok(Some(db).filter(|&db| db > -9999.0))
+}
+
+/// The PulseAudio volume in decibels for a volume where 1.0 is 100%, or `None` at 0%
+pub fn pulse_db(volume: f32) -> Option<f32> {
+	// PulseAudio volumes are cubic
+	Some(60.0 * volume.log10()).filter(|db| db.is_finite())
+}
+
+/// Changes the ALSA volume by `diff` percent. Returns the new volume in percent.
+#[cfg(feature = "alsa")]
+pub fn add(diff: i8) -> anyhow::Result<u8> {
+	let mixer = open_mixer()?;
 	let se_id = SelemId::new("Master", 0);
 	let selem = mixer.find_selem(&se_id).ok_or_else(|| anyhow!("Could not find alsa selem"))?;
 	let (min, max) = selem.get_playback_volume_range();
-	// Current volume
+	let range = (max - min) as f64;
+	// Current volume, rounded to whole percents the same way it's displayed, so that the raw
+	// steps not being exactly one percent don't make the displayed volume drift
 	let volume = selem.get_playback_volume(SelemChannelId::FrontLeft)?;
-	// A single percent volume
-	let step = (max - min) as f64 * 0.01;
-	let new_volume = volume + (step * f64::from(diff)).round() as i64;
-	selem.set_playback_volume_all(new_volume.max(min).min(max))?;
+	let percent = ((volume - min) as f64 / range * 100.0).round();
+	let percent = (percent + f64::from(diff)).max(0.0).min(100.0);
+	let new_volume = min + (percent / 100.0 * range).round() as i64;
+	selem.set_playback_volume_all(new_volume)?;
 	/*let _ = Command::new("pactl")
 	.arg("set-sink-volume")
 	.arg("@DEFAULT_SINK@")
 	.arg(format!("{:+}%", diff))
 	.spawn();**/
-	Ok(())
+	Ok(percent as u8)
 }
 
-pub fn set_device(controller: &mut SinkController, name: &str) -> anyhow::Result<()> {
+/// Makes `name` the default device, and moves all active streams to it
+#[cfg(feature = "pulse")]
+pub fn set_device<C>(controller: &mut C, name: &str) -> anyhow::Result<()>
+where
+	C: DeviceControl<DeviceInfo> + AppControl<ApplicationInfo>,
+{
 	// Set default device
 	match controller.set_default_device(name) {
 		Ok(false) => Notification::new("Couldn't set new device", None, None).show()?,
@@ -53,55 +209,217 @@ pub fn set_device(controller: &mut SinkController, name: &str) -> anyhow::Result
 	Ok(())
 }
 
-pub fn menu() -> Result<(), anyhow::Error> {
+/// The devices the pickers offer, and the labels they're shown with
+#[cfg(feature = "pulse")]
+#[derive(Debug, Clone)]
+pub struct DeviceMenu {
+	hidden: Vec<Regex>,
+	hide_monitors: bool,
+	/// Labels replacing the descriptions of devices and sound cards, by name
+	names: HashMap<String, String>,
+}
+
+#[cfg(feature = "pulse")]
+impl DeviceMenu {
+	/// Compiles the patterns of `config`. Invalid patterns are ignored, they're reported when
+	/// checking the configuration.
+	pub fn new(config: &DeviceMenuConfig) -> Self {
+		DeviceMenu {
+			hidden: config.hidden.iter().filter_map(|pattern| Regex::new(pattern).ok()).collect(),
+			hide_monitors: config.hide_monitors,
+			names: config.names.clone(),
+		}
+	}
+
+	/// The label of the device or sound card called `name`: the configured one, or else its
+	/// description, or else its name
+	pub fn label(&self, name: &str, description: &str) -> String {
+		match self.names.get(name) {
+			Some(label) => label.clone(),
+			None if !description.is_empty() => description.to_owned(),
+			None => name.to_owned(),
+		}
+	}
+
+	/// Whether the device called `name` is offered
+	fn shows(&self, name: &str, description: &str) -> bool {
+		// PulseAudio names the monitor of a sink after it
+		if self.hide_monitors && name.ends_with(".monitor") {
+			return false;
+		}
+		!self.hidden.iter().any(|pattern| pattern.is_match(name) || pattern.is_match(description))
+	}
+}
+
+/// Lets the user choose the audio output device, or the profile of a sound card with more than
+/// one, e.g. to switch a Bluetooth headset between call and music quality
+#[cfg(feature = "pulse")]
+pub fn menu(device_menu: &DeviceMenu) -> Result<(), anyhow::Error> {
 	let mut controller = SinkController::create()?;
+	let mut entries = devices(&mut controller, device_menu);
+	let cards = cards().unwrap_or_else(|e| {
+		eprintln!("Couldn't list sound cards: {}", e);
+		Vec::new()
+	});
+	for card in cards.iter().filter(|card| card.profiles.len() > 1) {
+		for (profile, label) in &card.profiles {
+			let active = if card.active.as_deref() == Some(profile) { " (active)" } else { "" };
+			entries.push((
+				format!("{}{} {}", PROFILE_ENTRY, card.name, profile),
+				format!(
+					"{}: {}{}",
+					device_menu.label(&card.name, &card.description),
+					label,
+					active
+				),
+			));
+		}
+	}
+	let choice = match Picker::detect().pick("Choose an audio device", &entries)? {
+		Some(choice) => choice,
+		None => return Ok(()),
+	};
+	match choice.strip_prefix(PROFILE_ENTRY).and_then(|entry| entry.split_once(' ')) {
+		Some((card, profile)) => set_profile(card, profile),
+		None => set_device(&mut controller, &choice),
+	}
+}
+
+/// Lets the user choose the audio input device
+#[cfg(feature = "pulse")]
+pub fn mic_menu(device_menu: &DeviceMenu) -> Result<(), anyhow::Error> {
+	choose(SourceController::create()?, "Choose a microphone", device_menu)
+}
+
+#[cfg(feature = "pulse")]
+fn choose<C>(mut controller: C, prompt: &str, device_menu: &DeviceMenu) -> anyhow::Result<()>
+where
+	C: DeviceControl<DeviceInfo> + AppControl<ApplicationInfo>,
+{
+	let devices = devices(&mut controller, device_menu);
 	// Launch device selection dialogue
-	let mut cmd = Command::new("zenity")
-		.args(&[
-			"--list",
-			"--text=Choose an audio device",
-			"--column=device-id",
-			"--column=Device name",
-			"--hide-column=1",
-			"--width=450",
-			"--height=250",
-		])
-		.stdin(Stdio::piped())
-		.stdout(Stdio::piped())
-		.spawn()?;
-	// Write device names to process stdin
-	{
-		let mut stdin = cmd.stdin.as_mut().unwrap();
-		for device in controller.list_devices().unwrap_or_default() {
-			writeln!(&mut stdin, "{}", device.name.unwrap_or_default())?;
-			writeln!(&mut stdin, "{}", device.description.unwrap_or_default())?;
+	if let Some(new_device) = Picker::detect().pick(prompt, &devices)? {
+		// Set audio device
+		set_device(&mut controller, &new_device)?;
+	}
+	Ok(())
+}
+
+/// The devices of `controller` that `device_menu` shows, as `(name, label)` pairs
+#[cfg(feature = "pulse")]
+fn devices<C: DeviceControl<DeviceInfo>>(
+	controller: &mut C,
+	device_menu: &DeviceMenu,
+) -> Vec<(String, String)> {
+	controller
+		.list_devices()
+		.unwrap_or_default()
+		.into_iter()
+		.map(|device| (device.name.unwrap_or_default(), device.description.unwrap_or_default()))
+		.filter(|(name, description)| device_menu.shows(name, description))
+		.map(|(name, description)| {
+			let label = device_menu.label(&name, &description);
+			(name, label)
+		})
+		.collect()
+}
+
+/// A sound card, and the profiles it can be switched to
+#[cfg(feature = "pulse")]
+struct Card {
+	name: String,
+	description: String,
+	/// The usable profiles, as `(name, description)` pairs
+	profiles: Vec<(String, String)>,
+	active: Option<String>,
+}
+
+/// Lists the sound cards through `pactl`, since pulsectl can't read or change card profiles
+#[cfg(feature = "pulse")]
+fn cards() -> anyhow::Result<Vec<Card>> {
+	let output = Command::new("pactl").args(&["list", "cards"]).env("LC_ALL", "C").output()?;
+	if !output.status.success() {
+		let stderr = String::from_utf8_lossy(&output.stderr);
+		return Err(anyhow!("pactl failed: {}", stderr.trim()));
+	}
+	let mut cards = Vec::new();
+	let mut section = String::new();
+	for line in String::from_utf8_lossy(&output.stdout).lines() {
+		let depth = line.chars().take_while(|&c| c == '\t').count();
+		let line = line.trim();
+		if line.starts_with("Card #") {
+			cards.push(Card {
+				name: String::new(),
+				description: String::new(),
+				profiles: Vec::new(),
+				active: None,
+			});
+			continue;
+		}
+		let card = match cards.last_mut() {
+			Some(card) => card,
+			None => continue,
+		};
+		match depth {
+			1 => {
+				section = line.trim_end_matches(':').to_owned();
+				if let Some(name) = line.strip_prefix("Name: ") {
+					card.name = name.to_owned();
+				} else if let Some(profile) = line.strip_prefix("Active Profile: ") {
+					card.active = Some(profile.to_owned());
+				}
+			}
+			2 if section == "Properties" => {
+				if let Some(description) = line.strip_prefix("device.description = ") {
+					card.description = description.trim_matches('"').to_owned();
+				}
+			}
+			2 if section == "Profiles" => {
+				// Leave out turning the card off, and profiles that can't be used right now
+				match line.split_once(": ") {
+					Some(("off", _)) => (),
+					Some((_, details)) if details.contains("available: no") => (),
+					Some((name, details)) => {
+						let label = details.rsplit_once(" (").map_or(details, |(label, _)| label);
+						card.profiles.push((name.to_owned(), label.to_owned()));
+					}
+					None => (),
+				}
+			}
+			_ => (),
 		}
 	}
-	// Get process stdout
-	let output = cmd.wait_with_output()?;
-	let new_device = String::from_utf8_lossy(&output.stdout);
-	let new_device = new_device.trim();
-	// Set audio device
-	if !new_device.is_empty() {
-		set_device(&mut controller, new_device)?;
+	Ok(cards)
+}
+
+/// Switches `card` to `profile`
+#[cfg(feature = "pulse")]
+pub fn set_profile(card: &str, profile: &str) -> anyhow::Result<()> {
+	let output = Command::new("pactl").args(&["set-card-profile", card, profile]).output()?;
+	if !output.status.success() {
+		let stderr = String::from_utf8_lossy(&output.stderr);
+		return Err(anyhow!("Couldn't switch to {}: {}", profile, stderr.trim()));
 	}
 	Ok(())
 }
 
-/// Toggles whether volume is muted
-pub fn mute() -> alsa::Result<()> {
-	let mixer = Mixer::new("default", false)?;
+/// Toggles whether the ALSA volume is muted
+#[cfg(feature = "alsa")]
+pub fn mute() -> anyhow::Result<()> {
+	let mixer = open_mixer()?;
 	let se_id = SelemId::new("Master", 0);
-	let selem = mixer.find_selem(&se_id).unwrap();
+	let selem = mixer.find_selem(&se_id).ok_or_else(|| anyhow!("Could not find alsa selem"))?;
 	let muted = selem.get_playback_switch(SelemChannelId::FrontLeft)? == 0;
 	selem.set_playback_switch_all(if muted { 1 } else { 0 })?;
 	Ok(())
 }
 
-pub fn icon(vol: u8) -> &'static str {
-	match vol {
-		0..=29 => "",
-		30..=59 => "",
-		_ => "",
-	}
+/// The icon for the volume `vol` in percent
+pub fn icon(vol: u8) -> Icon {
+	let level = match vol {
+		0..=29 => 0,
+		30..=59 => 1,
+		_ => 2,
+	};
+	icons::VOLUME[level]
 }
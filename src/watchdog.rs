@@ -0,0 +1,232 @@
+use std::{
+	sync::{Arc, Mutex},
+	thread,
+	time::{Duration, Instant},
+};
+
+use crate::config::WatchdogConfig;
+
+/// How often the watchdog checks for stalled widgets
+const INTERVAL: Duration = Duration::from_secs(1);
+
+/// The period `max_restarts` applies to
+const RESTART_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// Notices widgets whose periodic update hasn't finished in time, e.g. because a command they
+/// run hangs. Every widget reports a heartbeat around its updates, and a monitor thread logs
+/// the widgets that have been updating for longer than the timeout. Background workers that die
+/// or stop refreshing are restarted.
+#[derive(Debug, Clone)]
+pub struct Watchdog {
+	timeout: Option<Duration>,
+	stale_intervals: u32,
+	max_restarts: usize,
+	hearts: Arc<Mutex<Vec<Heart>>>,
+}
+
+#[derive(Debug)]
+struct Heart {
+	name: String,
+	/// When the update in progress started
+	busy_since: Option<Instant>,
+	/// Whether the update in progress has been reported as stalled
+	stalled: bool,
+	/// The background worker of the widget, if it has one
+	worker: Option<Worker>,
+}
+
+/// Spawns a widget's background worker, reporting to the given pulse
+type Start = Arc<dyn Fn(Pulse) + Send + Sync>;
+
+/// A thread refreshing a widget's data in the background
+struct Worker {
+	interval: Duration,
+	/// When the worker last finished refreshing, or was started
+	last_run: Instant,
+	/// Counts the workers started, so a replaced one can tell it should stop
+	generation: usize,
+	start: Start,
+	/// When the worker was restarted, within the restart period
+	restarts: Vec<Instant>,
+	/// Whether hitting the restart limit has been logged
+	throttled: bool,
+}
+
+impl std::fmt::Debug for Worker {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		f.debug_struct("Worker")
+			.field("interval", &self.interval)
+			.field("last_run", &self.last_run)
+			.field("generation", &self.generation)
+			.field("restarts", &self.restarts)
+			.finish()
+	}
+}
+
+impl Watchdog {
+	pub fn new(config: &WatchdogConfig) -> Self {
+		Watchdog {
+			timeout: Some(Duration::from_secs(config.timeout_secs)).filter(|_| config.enabled),
+			stale_intervals: config.stale_intervals.max(1),
+			max_restarts: config.max_restarts as usize,
+			hearts: Arc::default(),
+		}
+	}
+
+	pub fn enabled(&self) -> bool {
+		self.timeout.is_some()
+	}
+
+	/// Adds the widget called `name`. Returns the heartbeat it reports its updates to.
+	pub fn register(&self, name: &str) -> Heartbeat {
+		let mut hearts = self.hearts.lock().unwrap();
+		hearts.push(Heart {
+			name: name.to_owned(),
+			busy_since: None,
+			stalled: false,
+			worker: None,
+		});
+		Heartbeat { hearts: self.hearts.clone(), slot: hearts.len() - 1 }
+	}
+
+	/// Starts checking the heartbeats in the background, if enabled
+	pub fn start(&self) {
+		let timeout = match self.timeout {
+			Some(timeout) => timeout,
+			None => return,
+		};
+		let watchdog = self.clone();
+		thread::spawn(move || loop {
+			thread::sleep(INTERVAL);
+			let mut restarts = Vec::new();
+			for (slot, heart) in watchdog.hearts.lock().unwrap().iter_mut().enumerate() {
+				let busy = heart.busy_since.map_or(Duration::default(), |since| since.elapsed());
+				if busy > timeout && !heart.stalled {
+					heart.stalled = true;
+					eprintln!(
+						"{} hasn't finished updating in {} seconds",
+						heart.name,
+						busy.as_secs()
+					);
+				}
+				if let Some(worker) = &mut heart.worker {
+					if let Some(start) = watchdog.restart(&heart.name, worker) {
+						let pulse = Pulse {
+							hearts: watchdog.hearts.clone(),
+							slot,
+							generation: worker.generation,
+						};
+						restarts.push((start, pulse));
+					}
+				}
+			}
+			// The new workers report to the hearts, so they're started once the lock is released
+			for (start, pulse) in restarts {
+				start(pulse);
+			}
+		});
+	}
+
+	/// Checks whether the widget `name`'s worker has stopped refreshing. Returns the function
+	/// starting its replacement if it should be restarted.
+	fn restart(&self, name: &str, worker: &mut Worker) -> Option<Start> {
+		let idle = worker.last_run.elapsed();
+		if idle <= worker.interval * self.stale_intervals + INTERVAL {
+			return None;
+		}
+		worker.restarts.retain(|restart| restart.elapsed() < RESTART_PERIOD);
+		if worker.restarts.len() >= self.max_restarts {
+			if !worker.throttled {
+				worker.throttled = true;
+				eprintln!(
+					"The {} worker hasn't refreshed in {} seconds, but has been restarted too \
+					 often. Retrying later.",
+					name,
+					idle.as_secs()
+				);
+			}
+			return None;
+		}
+		eprintln!(
+			"Restarting the {} worker, it hasn't refreshed in {} seconds",
+			name,
+			idle.as_secs()
+		);
+		worker.generation += 1;
+		worker.last_run = Instant::now();
+		worker.restarts.push(Instant::now());
+		worker.throttled = false;
+		Some(worker.start.clone())
+	}
+
+	/// The names of the widgets that are stalled
+	pub fn stalled(&self) -> Vec<String> {
+		let hearts = self.hearts.lock().unwrap();
+		hearts.iter().filter(|heart| heart.stalled).map(|heart| heart.name.clone()).collect()
+	}
+}
+
+/// Reports the updates of a widget to the watchdog
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+	hearts: Arc<Mutex<Vec<Heart>>>,
+	slot: usize,
+}
+
+impl Heartbeat {
+	/// Runs an update of the widget, reporting when it starts and finishes
+	pub fn beat<T, F: FnOnce() -> T>(&self, update: F) -> T {
+		self.set(Some(Instant::now()));
+		let result = update();
+		self.set(None);
+		result
+	}
+
+	fn set(&self, busy_since: Option<Instant>) {
+		let mut hearts = self.hearts.lock().unwrap();
+		let heart = &mut hearts[self.slot];
+		if heart.stalled && busy_since.is_none() {
+			eprintln!("{} has recovered", heart.name);
+		}
+		heart.busy_since = busy_since;
+		heart.stalled = false;
+	}
+
+	/// Starts the widget's background worker with `start`, which spawns a thread refreshing
+	/// every `interval` and reporting to the given pulse. The worker is started again if it dies
+	/// or stops refreshing.
+	pub fn supervise<F: Fn(Pulse) + Send + Sync + 'static>(&self, interval: Duration, start: F) {
+		let start: Start = Arc::new(start);
+		self.hearts.lock().unwrap()[self.slot].worker = Some(Worker {
+			interval,
+			last_run: Instant::now(),
+			generation: 0,
+			start: start.clone(),
+			restarts: Vec::new(),
+			throttled: false,
+		});
+		start(Pulse { hearts: self.hearts.clone(), slot: self.slot, generation: 0 });
+	}
+}
+
+/// Reports the refreshes of a background worker to the watchdog
+pub struct Pulse {
+	hearts: Arc<Mutex<Vec<Heart>>>,
+	slot: usize,
+	generation: usize,
+}
+
+impl Pulse {
+	/// Reports a finished refresh. Returns whether the worker is still current, or has been
+	/// replaced and should stop.
+	pub fn beat(&self) -> bool {
+		let mut hearts = self.hearts.lock().unwrap();
+		match &mut hearts[self.slot].worker {
+			Some(worker) if worker.generation == self.generation => {
+				worker.last_run = Instant::now();
+				true
+			}
+			_ => false,
+		}
+	}
+}
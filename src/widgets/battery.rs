@@ -0,0 +1,482 @@
+use std::{
+	fs,
+	sync::atomic::{AtomicBool, Ordering},
+	time::Duration as StdDuration,
+};
+
+use unixbar::{
+	bfmt,
+	format::{ClickAction, Format, MouseButton},
+	widget::Periodic,
+	Duration,
+};
+
+use super::{catch, staggered, Bar, BarWidget, Context};
+#[cfg(feature = "upower")]
+use crate::upower::Device;
+use crate::{
+	block::Block,
+	color::Color,
+	config::{BatteryBackend, BatteryConfig, BlockConfig, IconStyle, Urgency},
+	events::{Event, Events},
+	hardware::Hardware,
+	icons,
+	notify::Notification,
+	sampler::{Metric, Sampler},
+	template,
+	threshold::{Alert, Direction, ThresholdNotifier},
+};
+
+/// How far in percent the capacity has to recover before a notification is sent again
+const HYSTERESIS: f64 = 2.0;
+
+/// Shows the battery capacity and charging state, and notifies when the battery runs low
+pub struct Battery {
+	config: BatteryConfig,
+	block: Block,
+	events: Events,
+	hardware: Hardware,
+	source: Box<dyn BatterySource + Send + Sync>,
+	/// Sends the critical notification
+	low: ThresholdNotifier,
+	/// Sends the charged notification, if enabled
+	charged: Option<ThresholdNotifier>,
+	/// Whether the flash color is shown in the current tick
+	flashing: AtomicBool,
+}
+
+impl Battery {
+	pub fn new(cx: &Context) -> Self {
+		let config = &cx.config.battery;
+		let source: Box<dyn BatterySource + Send + Sync> = match config.backend {
+			BatteryBackend::Systemstat => Box::new(SystemBattery::new(cx.sampler)),
+			#[cfg(feature = "upower")]
+			BatteryBackend::Upower => match Device::display() {
+				Ok(_) => Box::new(UPowerBattery),
+				Err(e) => {
+					eprintln!(
+						"UPower is unavailable, reading the battery through systemstat: {}",
+						e
+					);
+					Box::new(SystemBattery::new(cx.sampler))
+				}
+			},
+			#[cfg(not(feature = "upower"))]
+			BatteryBackend::Upower => {
+				eprintln!("UPower support isn't built in, reading the battery through systemstat");
+				Box::new(SystemBattery::new(cx.sampler))
+			}
+		};
+		Battery {
+			source,
+			config: config.clone(),
+			block: cx.block.clone(),
+			events: cx.events.clone(),
+			hardware: cx.hardware,
+			low: ThresholdNotifier::new(
+				Direction::Below,
+				f64::from(config.threshold),
+				HYSTERESIS,
+				Alert {
+					summary: String::from("Battery level critical"),
+					body: String::from("Connect to power source immediately"),
+					icon: config.icon.clone(),
+					urgency: config.urgency,
+					value_hint: config.value_hint,
+				},
+			),
+			charged: config.full_level.map(|level| {
+				ThresholdNotifier::new(
+					Direction::Above,
+					f64::from(level),
+					HYSTERESIS,
+					Alert {
+						summary: String::from("Battery charged"),
+						body: String::from("The power source can be disconnected"),
+						icon: String::from("battery-full-charged"),
+						urgency: Urgency::Normal,
+						value_hint: false,
+					},
+				)
+			}),
+			flashing: AtomicBool::new(false),
+		}
+	}
+
+	/// Whether the battery is critically low
+	fn critical(&self, state: BatteryState) -> bool {
+		let low = state.warning.unwrap_or(state.capacity <= self.config.threshold);
+		low && !state.charging
+	}
+
+	/// Applies the block settings for this tick, alternating the background while critical
+	fn apply(&self, state: BatteryState, format: Format) -> Format {
+		let flash = self.config.flash && self.critical(state);
+		// Toggle every tick, and start with the flash color
+		if flash && !self.flashing.fetch_xor(true, Ordering::AcqRel) {
+			let background = Some(self.config.flash_color.clone());
+			let config = BlockConfig { background, ..self.block.config().clone() };
+			self.block.apply_with(&config, format)
+		} else {
+			if !flash {
+				self.flashing.store(false, Ordering::Release);
+			}
+			self.block.apply(format)
+		}
+	}
+
+	/// Sends notifications if needed
+	fn notify(&self, state: BatteryState) -> anyhow::Result<()> {
+		let capacity = f64::from(state.capacity);
+		if state.charging {
+			self.low.reset();
+		} else {
+			let crossed = match state.warning {
+				// The source decides by itself when the battery is low
+				Some(warning) => self.low.latch(warning, !warning),
+				None => self.low.update(capacity),
+			};
+			if crossed {
+				self.low.notify(capacity)?;
+				self.events.emit(Event::BatteryCritical { capacity: state.capacity });
+			}
+		}
+		// Send notification when charged up to the configured level
+		if let Some(charged) = &self.charged {
+			if state.charging {
+				charged.check(capacity)?;
+			} else {
+				charged.reset();
+			}
+		}
+		Ok(())
+	}
+}
+
+impl BarWidget for Battery {
+	/// Hidden on machines without a battery
+	fn visible(&self) -> bool {
+		self.hardware.battery
+	}
+
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		bar.register_fn("battery_limit", || show_limit().unwrap_or(()));
+		bar.add(Periodic::new(
+			Duration::from_secs(1),
+			staggered(self.block.schedule(), move || {
+				catch(&self.block, || {
+					let state = match self.source.read() {
+						Some(state) => state,
+						None => return Ok(bfmt![text[""]]),
+					};
+					self.notify(state)?;
+					let format = render_battery(
+						state,
+						&self.config,
+						self.block.icon_style(),
+						|full, short| self.block.compact(full, short),
+					);
+					Ok(self.apply(state, format))
+				})
+			}),
+		));
+	}
+}
+
+/// A reading of the battery state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatteryState {
+	/// Remaining capacity in percent
+	pub capacity: u8,
+	/// Whether the system is connected to a power source
+	pub charging: bool,
+	/// The capacity in percent the firmware stops charging at, if one is set
+	pub limit: Option<u8>,
+	/// Estimated time until the battery is empty, or full while charging
+	pub time_left: Option<StdDuration>,
+	/// Whether the source considers the battery low, if it decides that itself
+	pub warning: Option<bool>,
+}
+
+/// A source of battery readings
+pub trait BatterySource {
+	/// Reads the current battery state, or `None` if there is no battery
+	fn read(&self) -> Option<BatteryState>;
+}
+
+/// Reads the battery state through systemstat
+pub struct SystemBattery {
+	sampler: Sampler,
+}
+
+impl SystemBattery {
+	pub fn new(sampler: &Sampler) -> Self {
+		sampler.watch(Metric::Battery);
+		SystemBattery { sampler: sampler.clone() }
+	}
+}
+
+impl BatterySource for SystemBattery {
+	fn read(&self) -> Option<BatteryState> {
+		let snapshot = self.sampler.get();
+		let state = snapshot.battery().ok()?;
+		let (battery, charging) = (&state.life, state.on_ac_power);
+		let capacity = (battery.remaining_capacity * 100.0).round() as u8;
+		Some(BatteryState {
+			capacity,
+			charging,
+			limit: charge_limit(),
+			// systemstat only estimates the time until empty
+			time_left: Some(battery.remaining_time).filter(|_| !charging),
+			warning: None,
+		})
+	}
+}
+
+/// Reads the battery state from UPower
+#[cfg(feature = "upower")]
+pub struct UPowerBattery;
+
+#[cfg(feature = "upower")]
+impl BatterySource for UPowerBattery {
+	fn read(&self) -> Option<BatteryState> {
+		let device = Device::display().ok()??;
+		Some(BatteryState {
+			capacity: device.percentage.round() as u8,
+			charging: device.charging,
+			limit: charge_limit(),
+			time_left: device.time_left,
+			warning: Some(device.warning),
+		})
+	}
+}
+
+/// Reads the charge limit from sysfs. Returns `None` if no battery exposes
+/// `charge_control_end_threshold`, or if charging isn't limited.
+pub fn charge_limit() -> Option<u8> {
+	fs::read_dir("/sys/class/power_supply")
+		.ok()?
+		.filter_map(Result::ok)
+		.filter(|entry| entry.file_name().to_string_lossy().starts_with("BAT"))
+		.find_map(|entry| {
+			let threshold = fs::read_to_string(entry.path().join("charge_control_end_threshold"));
+			threshold.ok()?.trim().parse().ok()
+		})
+		.filter(|&limit| limit < 100)
+}
+
+/// Shows a notification with the current charge limit
+pub fn show_limit() -> anyhow::Result<()> {
+	let body = match charge_limit() {
+		Some(limit) => format!("Charging stops at {}%", limit),
+		None => return Ok(()),
+	};
+	Notification::new("Battery charge limit", Some(body.as_str()), Some("battery")).show()?;
+	Ok(())
+}
+
+/// Renders the battery widget for the given state, with the icons drawn in `style`. `compact` is
+/// given the width of the full and the compact layout, and decides whether to leave out the
+/// charging state and time.
+pub fn render_battery(
+	state: BatteryState,
+	config: &BatteryConfig,
+	style: IconStyle,
+	compact: impl FnOnce(usize, usize) -> bool,
+) -> Format {
+	let icon = match &config.icons {
+		Some(custom) => ramp(custom, state.capacity).map(String::as_str).unwrap_or(""),
+		None => ramp(&icons::BATTERY, state.capacity).map_or("", |icon| style.pick(*icon)),
+	};
+	let color = ramp(&config.colors, state.capacity).map(Color::as_str).unwrap_or("#50FF00");
+	let charging = match state.limit {
+		// Charging has stopped at the limit, don't make it look like it's stuck
+		Some(limit) if state.charging && state.capacity >= limit.saturating_sub(1) => {
+			config.limited_icon.as_deref().unwrap_or_else(|| style.pick(icons::LIMITED))
+		}
+		_ if state.charging => {
+			config.charging_icon.as_deref().unwrap_or_else(|| style.pick(icons::CHARGING))
+		}
+		_ => "",
+	};
+	let time = match state.time_left {
+		Some(time) if config.show_time => {
+			let minutes = time.as_secs() / 60;
+			format!(" ({}:{:02})", minutes / 60, minutes % 60)
+		}
+		_ => String::new(),
+	};
+	let values: &[(&str, &dyn ToString)] =
+		&[("charging", &charging), ("icon", &icon), ("value", &state.capacity), ("time", &time)];
+	let full = template::fill(&config.format, values);
+	let short = template::fill("{icon} {value}%", values);
+	let text = match compact(full.chars().count(), short.chars().count()) {
+		true => short,
+		false => full,
+	};
+	// Only offer showing the limit when there is one
+	match state.limit {
+		Some(_) => bfmt![
+			click[MouseButton::Left => fn "battery_limit"]
+			fg[color]
+			fmt["{}", text]
+		],
+		None => bfmt![
+			fg[color]
+			fmt["{}", text]
+		],
+	}
+}
+
+/// Picks the item for a percentage out of `items`, ordered from lowest to highest. Each item
+/// covers an equal share of the range.
+pub fn ramp<T>(items: &[T], percent: u8) -> Option<&T> {
+	match items.len() {
+		0 => None,
+		len => items.get((usize::from(percent) * len / 100).min(len - 1)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// The text of `format`, without its styling
+	fn text(format: &Format) -> String {
+		match format {
+			Format::Str(text) | Format::UnescapedStr(text) => text.clone(),
+			Format::Concat(parts) => parts.iter().map(|part| text(part)).collect(),
+			Format::Align(_, inner)
+			| Format::FgColor(_, inner)
+			| Format::BgColor(_, inner)
+			| Format::NoSeparator(inner)
+			| Format::Padding(_, inner)
+			| Format::Clickable(_, inner) => text(inner),
+		}
+	}
+
+	/// The outermost foreground color of `format`, if it has one
+	fn foreground(format: &Format) -> Option<&str> {
+		match format {
+			Format::FgColor(color, _) => Some(color.as_str()),
+			Format::Str(_) | Format::UnescapedStr(_) => None,
+			Format::Concat(parts) => parts.iter().find_map(|part| foreground(part)),
+			Format::Align(_, inner)
+			| Format::BgColor(_, inner)
+			| Format::NoSeparator(inner)
+			| Format::Padding(_, inner)
+			| Format::Clickable(_, inner) => foreground(inner),
+		}
+	}
+
+	fn state(capacity: u8, charging: bool) -> BatteryState {
+		BatteryState { capacity, charging, limit: None, time_left: None, warning: None }
+	}
+
+	fn render(state: BatteryState) -> Format {
+		render_battery(state, &BatteryConfig::default(), IconStyle::Glyphs, |_, _| false)
+	}
+
+	#[test]
+	fn color_buckets() {
+		let buckets = [
+			(5, "#FF4000", icons::BATTERY[0]),
+			(25, "#FFAE00", icons::BATTERY[1]),
+			(45, "#FFF600", icons::BATTERY[2]),
+			(65, "#A8FF00", icons::BATTERY[3]),
+			(95, "#50FF00", icons::BATTERY[4]),
+		];
+		for &(capacity, color, icon) in &buckets {
+			let format = render(state(capacity, false));
+			assert_eq!(foreground(&format), Some(color), "capacity {}", capacity);
+			assert_eq!(text(&format), format!("{} {}%", icon.glyph, capacity));
+		}
+	}
+
+	#[test]
+	fn charging() {
+		let format = render(state(50, true));
+		assert_eq!(foreground(&format), Some("#FFF600"));
+		let expected = format!("{}{} 50%", icons::CHARGING.glyph, icons::BATTERY[2].glyph);
+		assert_eq!(text(&format), expected);
+	}
+
+	#[test]
+	fn charging_stopped_at_limit() {
+		let limited = BatteryState { limit: Some(80), ..state(79, true) };
+		let expected = format!("{}{} 79%", icons::LIMITED.glyph, icons::BATTERY[3].glyph);
+		assert_eq!(text(&render(limited)), expected);
+		// Still charging below the limit
+		let below = BatteryState { limit: Some(80), ..state(60, true) };
+		let expected = format!("{}{} 60%", icons::CHARGING.glyph, icons::BATTERY[3].glyph);
+		assert_eq!(text(&render(below)), expected);
+	}
+
+	#[test]
+	fn ramp_edges() {
+		let items = [0, 1, 2, 3, 4];
+		let edges = [
+			(0, 0),
+			(19, 0),
+			(20, 1),
+			(39, 1),
+			(40, 2),
+			(59, 2),
+			(60, 3),
+			(79, 3),
+			(80, 4),
+			(100, 4),
+		];
+		for &(percent, expected) in &edges {
+			assert_eq!(ramp(&items, percent), Some(&expected), "{}%", percent);
+		}
+		// Out of range readings stick to the last item
+		assert_eq!(ramp(&items, 120), Some(&4));
+		assert_eq!(ramp::<u8>(&[], 50), None);
+	}
+
+	#[test]
+	fn custom_icons_with_charging() {
+		let config = BatteryConfig {
+			icons: Some(vec![String::from("low"), String::from("high")]),
+			charging_icon: Some(String::from("+")),
+			..BatteryConfig::default()
+		};
+		let cases = [(0, "low"), (49, "low"), (50, "high"), (100, "high")];
+		for &(capacity, icon) in &cases {
+			for &charging in &[false, true] {
+				let format = render_battery(
+					state(capacity, charging),
+					&config,
+					IconStyle::Glyphs,
+					|_, _| false,
+				);
+				let sign = if charging { "+" } else { "" };
+				assert_eq!(text(&format), format!("{}{} {}%", sign, icon, capacity));
+			}
+		}
+	}
+
+	#[test]
+	fn too_few_icons() {
+		// A single icon covers the whole range, and no icons leave it out
+		for (custom, expected) in vec![(vec![String::from("bat")], "bat 0%"), (vec![], " 0%")] {
+			let config = BatteryConfig { icons: Some(custom), ..BatteryConfig::default() };
+			let format = render_battery(state(0, false), &config, IconStyle::Glyphs, |_, _| false);
+			assert_eq!(text(&format), expected);
+		}
+		// Colors run out the same way
+		let config = BatteryConfig { colors: Vec::new(), ..BatteryConfig::default() };
+		let format = render_battery(state(0, true), &config, IconStyle::Glyphs, |_, _| false);
+		assert_eq!(foreground(&format), Some("#50FF00"));
+		let expected = format!("{}{} 0%", icons::CHARGING.glyph, icons::BATTERY[0].glyph);
+		assert_eq!(text(&format), expected);
+	}
+
+	#[test]
+	fn compact_leaves_out_charging() {
+		let config = BatteryConfig::default();
+		let format = render_battery(state(50, true), &config, IconStyle::Text, |_, _| true);
+		assert_eq!(text(&format), "BAT 50%");
+	}
+}
@@ -0,0 +1,102 @@
+use std::{
+	io::ErrorKind as IoErrorKind,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc,
+	},
+};
+
+use unixbar::{bfmt, format::MouseButton, widget::backlight::Backlight};
+
+use super::{catch, clickable, lock_icon, Bar, BarWidget, Context};
+use crate::{
+	block::Block,
+	config::{GaugeConfig, LevelDisplay},
+	gauge,
+	hardware::Hardware,
+	icons,
+	osd::Osd,
+	template,
+};
+
+/// Shows the screen backlight brightness, and adjusts it on scroll
+pub struct Brightness {
+	block: Block,
+	hardware: Hardware,
+	format: String,
+	display: LevelDisplay,
+	gauge: GaugeConfig,
+	osd: Osd,
+	/// Whether scrolling is ignored
+	locked: Arc<AtomicBool>,
+}
+
+impl Brightness {
+	pub fn new(cx: &Context) -> Self {
+		Brightness {
+			block: cx.block.clone(),
+			hardware: cx.hardware,
+			format: cx.config.brightness.format.clone(),
+			display: cx.config.brightness.display,
+			gauge: cx.config.gauge.clone(),
+			osd: Osd::new(&cx.config.osd, "Brightness", "display-brightness"),
+			locked: Arc::new(AtomicBool::new(cx.config.brightness.locked)),
+		}
+	}
+}
+
+impl BarWidget for Brightness {
+	/// Hidden on machines without a backlight
+	fn visible(&self) -> bool {
+		self.hardware.backlight
+	}
+
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let (up, down) = (self.osd.clone(), self.osd.clone());
+		let (up_lock, down_lock, lock) =
+			(self.locked.clone(), self.locked.clone(), self.locked.clone());
+		bar.register_fn("bright_up", move || adjust(0.05, &up, &up_lock))
+			.register_fn("bright_down", move || adjust(-0.05, &down, &down_lock))
+			.register_fn("bright_lock", move || {
+				lock.fetch_xor(true, Ordering::Relaxed);
+			});
+		bar.add(Backlight::new(move || {
+			catch(&self.block, || match Backlight::get() {
+				Ok(brightness) => {
+					let lock = lock_icon(&self.block, &self.locked);
+					let icon = self.block.icon(icons::BRIGHTNESS);
+					let value = format!("{:.0}", brightness * 100.0);
+					let text = match self.display {
+						LevelDisplay::Gauge => String::from(icon),
+						_ => template::fill(&self.format, &[("icon", &icon), ("value", &value)]),
+					};
+					let mut format = bfmt![fg["#ffff55"] fmt["{}{}", lock, text]];
+					if self.display != LevelDisplay::Percent {
+						format = gauge::append(format, f64::from(brightness), &self.gauge);
+					}
+					Ok(self.block.apply(clickable(
+						format,
+						&[
+							(MouseButton::ScrollUp, "bright_up"),
+							(MouseButton::ScrollDown, "bright_down"),
+							(MouseButton::Right, "bright_lock"),
+						],
+					)))
+				}
+				Err(e) if e.kind() == IoErrorKind::NotFound => {
+					Ok(self.block.apply(bfmt![text[""]]))
+				}
+				Err(e) => Err(e.into()),
+			})
+		}));
+	}
+}
+
+/// Changes the brightness by `diff` unless `locked`, and shows the new brightness
+fn adjust(diff: f32, osd: &Osd, locked: &AtomicBool) {
+	if !locked.load(Ordering::Relaxed) && Backlight::adjust(diff).is_ok() {
+		if let Ok(brightness) = Backlight::get() {
+			osd.show((brightness * 100.0).round() as u8);
+		}
+	}
+}
@@ -0,0 +1,61 @@
+use unixbar::{
+	bfmt,
+	format::{Format, MouseButton},
+	widget::Text,
+};
+
+use super::{Bar, BarWidget, Context};
+use crate::{
+	block::{self, Block},
+	config::{ButtonConfig, ButtonsConfig},
+};
+
+/// A row of launcher buttons, each running its configured commands when clicked, e.g. to open a
+/// power menu
+pub struct Buttons {
+	config: ButtonsConfig,
+	block: Block,
+}
+
+impl Buttons {
+	pub fn new(cx: &Context) -> Self {
+		Buttons { config: cx.config.buttons.clone(), block: cx.block.clone() }
+	}
+}
+
+/// Draws `button`, bound to its commands
+fn render(button: &ButtonConfig) -> Format {
+	let mut format = match &button.color {
+		Some(color) => bfmt![fg[color.as_str()] fmt["{}", button.text]],
+		None => bfmt![fmt["{}", button.text]],
+	};
+	let commands = [
+		(MouseButton::Left, &button.left),
+		(MouseButton::Middle, &button.middle),
+		(MouseButton::Right, &button.right),
+	];
+	for (mouse_button, command) in &commands {
+		if let Some(command) = command {
+			format = block::bind(format, mouse_button, command);
+		}
+	}
+	format
+}
+
+impl BarWidget for Buttons {
+	/// Hidden until buttons are configured
+	fn visible(&self) -> bool {
+		!self.config.items.is_empty()
+	}
+
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let mut parts = Vec::new();
+		for (i, button) in self.config.items.iter().enumerate() {
+			if i > 0 {
+				parts.push(Box::new(bfmt![fmt["{}", self.config.separator]]));
+			}
+			parts.push(Box::new(render(button)));
+		}
+		bar.add(Text::new(self.block.apply(Format::Concat(parts))));
+	}
+}
@@ -0,0 +1,209 @@
+use std::{
+	convert::TryFrom,
+	env,
+	fmt::{self, Write},
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc, Mutex,
+	},
+};
+
+use anyhow::anyhow;
+use chrono::{DateTime, Local, Locale, TimeZone, Utc};
+use unixbar::{
+	bfmt,
+	format::{ClickAction, MouseButton},
+	widget::Periodic,
+	Duration,
+};
+
+use super::{catch, report, staggered, Bar, BarWidget, Context};
+use crate::{
+	block::{self, Block},
+	clipboard,
+	config::{ClipboardTool, ClockConfig, Timezone},
+	icons,
+};
+
+/// Shows the date and time. Scrolling cycles through the configured formats.
+pub struct Clock {
+	config: ClockConfig,
+	block: Block,
+	/// The formats cycled through, adjusted for 12-hour time if configured
+	formats: Vec<String>,
+	locale: Locale,
+	/// The index of the format shown
+	current: Arc<AtomicUsize>,
+	/// The text last shown, which is what gets copied
+	shown: Arc<Mutex<String>>,
+	clipboard: Option<ClipboardTool>,
+}
+
+impl Clock {
+	pub fn new(cx: &Context) -> Self {
+		let config = &cx.config.clock;
+		let mut formats = config.formats.clone();
+		if formats.is_empty() {
+			formats.push(String::from("{icon} %d/%m %H:%M"));
+		}
+		let icon = cx.block.icon(icons::CLOCK);
+		formats = formats.iter().map(|format| format.replace("{icon}", icon)).collect();
+		if config.week_number {
+			formats = formats.iter().map(|format| format!("{} W%V", format)).collect();
+		}
+		if config.twelve_hour {
+			formats = formats.iter().map(|format| twelve_hour(format)).collect();
+		}
+		Clock {
+			config: config.clone(),
+			block: cx.block.clone(),
+			formats,
+			locale: locale(config.locale.as_deref()),
+			current: Arc::new(AtomicUsize::new(0)),
+			shown: Arc::default(),
+			clipboard: cx.config.clipboard,
+		}
+	}
+}
+
+impl BarWidget for Clock {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let count = self.formats.len();
+		let (next, prev) = (self.current.clone(), self.current.clone());
+		bar.register_fn("clock_next", move || {
+			let _ =
+				next.fetch_update(Ordering::AcqRel, Ordering::Acquire, |i| Some((i + 1) % count));
+		})
+		.register_fn("clock_prev", move || {
+			let _ = prev.fetch_update(Ordering::AcqRel, Ordering::Acquire, |i| {
+				Some((i + count - 1) % count)
+			});
+		});
+		let (tool, shown, icon) =
+			(self.clipboard, self.shown.clone(), self.block.icon(icons::CLOCK));
+		bar.register_fn("clock_copy", move || {
+			// Leave out the clock icon, and other icons, which are in the private use area
+			let text: String = shown
+				.lock()
+				.unwrap()
+				.replace(icon, "")
+				.chars()
+				.filter(|c| !('\u{e000}'..='\u{f8ff}').contains(c))
+				.collect();
+			report("Couldn't copy the time", clipboard::copy(tool, text.trim()))
+		});
+		bar.add(Periodic::new(
+			Duration::from_secs(1),
+			staggered(self.block.schedule(), move || {
+				catch(&self.block, || {
+					let format = &self.formats[self.current.load(Ordering::Acquire)];
+					let now = Utc::now();
+					// DST is handled by the timezone database
+					let time = match self.config.timezone {
+						Some(Timezone(tz)) => {
+							format_time(&now.with_timezone(&tz), format, self.locale)
+						}
+						None => format_time(&now.with_timezone(&Local), format, self.locale),
+					};
+					let time = match time {
+						Some(time) => time,
+						None => return Err(anyhow!("invalid clock format {}", format)),
+					};
+					*self.shown.lock().unwrap() = time.clone();
+					let format = bfmt![
+						click[MouseButton::Middle => fn "clock_copy"]
+						click[MouseButton::ScrollUp => fn "clock_prev"]
+						click[MouseButton::ScrollDown => fn "clock_next"]
+						fmt["{}", time]
+					];
+					let format = match &self.config.on_click {
+						Some(command) => block::bind(format, &MouseButton::Left, command),
+						None => format,
+					};
+					Ok(self.block.apply(format))
+				})
+			}),
+		));
+	}
+}
+
+/// Formats `time` with the strftime `format`. Returns `None` if the format has unknown
+/// specifiers, which make formatting fail rather than being left as they are.
+pub fn format_time<Tz: TimeZone>(
+	time: &DateTime<Tz>,
+	format: &str,
+	locale: Locale,
+) -> Option<String>
+where
+	Tz::Offset: fmt::Display,
+{
+	let mut text = String::new();
+	write!(text, "{}", time.format_localized(format, locale)).ok()?;
+	Some(text)
+}
+
+/// Changes a strftime format to show 12-hour time with AM or PM
+pub fn twelve_hour(format: &str) -> String {
+	let format = format.replace("%H", "%I").replace("%R", "%I:%M").replace("%T", "%I:%M:%S");
+	match format.contains("%p") || format.contains("%P") || !format.contains("%I") {
+		true => format,
+		false => format!("{} %p", format),
+	}
+}
+
+/// Finds the locale called `name`, or the one set in the environment. Falls back to the POSIX
+/// locale when there's none, as when the C library locale isn't set.
+pub fn locale(name: Option<&str>) -> Locale {
+	let from_env = || {
+		["LC_ALL", "LC_TIME", "LANG"]
+			.iter()
+			.find_map(|var| env::var(var).ok().filter(|v| !v.is_empty()))
+	};
+	let name = match name.map(String::from).or_else(from_env) {
+		Some(name) => name,
+		None => return Locale::POSIX,
+	};
+	// Strip the encoding and modifier, as in `de_DE.UTF-8@euro`
+	let base = name.split(|c| c == '.' || c == '@').next().unwrap_or_default();
+	match Locale::try_from(base) {
+		Ok(locale) => locale,
+		Err(_) => {
+			if base != "C" && base != "POSIX" {
+				eprintln!("warning: unknown locale {}, using POSIX", name);
+			}
+			Locale::POSIX
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono_tz::Europe::Berlin;
+
+	use super::*;
+
+	#[test]
+	fn uncommon_specifiers() {
+		// The Sunday before the first ISO week of 2021
+		let winter = Berlin.with_ymd_and_hms(2021, 1, 3, 12, 0, 0).unwrap();
+		let text = format_time(&winter, "W%V %j %Z", Locale::POSIX);
+		assert_eq!(text.as_deref(), Some("W53 003 CET"));
+		let summer = Berlin.with_ymd_and_hms(2021, 7, 1, 12, 0, 0).unwrap();
+		let text = format_time(&summer, "W%V %j %Z %d/%m %H:%M", Locale::POSIX);
+		assert_eq!(text.as_deref(), Some("W26 182 CEST 01/07 12:00"));
+	}
+
+	#[test]
+	fn unknown_specifier() {
+		let time = Berlin.with_ymd_and_hms(2021, 1, 3, 12, 0, 0).unwrap();
+		assert_eq!(format_time(&time, "%J", Locale::POSIX), None);
+	}
+
+	#[test]
+	fn twelve_hour_formats() {
+		assert_eq!(twelve_hour("%d/%m %H:%M"), "%d/%m %I:%M %p");
+		assert_eq!(twelve_hour("%T"), "%I:%M:%S %p");
+		assert_eq!(twelve_hour("%I:%M %P"), "%I:%M %P");
+		assert_eq!(twelve_hour("%d/%m"), "%d/%m");
+	}
+}
@@ -0,0 +1,28 @@
+use unixbar::{bfmt, format::MouseButton, widget::Text};
+
+use super::{clickable, Bar, BarWidget, Context};
+use crate::{block::Block, config::FlairConfig};
+
+/// Decorative text, a kaomoji unless configured otherwise
+pub struct Flair {
+	config: FlairConfig,
+	block: Block,
+}
+
+impl Flair {
+	pub fn new(cx: &Context) -> Self {
+		Flair { config: cx.config.flair.clone(), block: cx.block.clone() }
+	}
+}
+
+impl BarWidget for Flair {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let text = &self.config.text;
+		let format = match &self.config.color {
+			Some(color) => bfmt![fg[color.as_str()] fmt["{}", text]],
+			None => bfmt![fmt["{}", text]],
+		};
+		let format = clickable(format, &[(MouseButton::Left, "compact_toggle")]);
+		bar.add(Text::new(self.block.apply(format)));
+	}
+}
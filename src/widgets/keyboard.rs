@@ -0,0 +1,125 @@
+use std::process::Command;
+
+use anyhow::anyhow;
+use unixbar::{
+	bfmt,
+	format::{ClickAction, Format, MouseButton},
+	widget::Periodic,
+	Duration,
+};
+
+use super::{report, staggered, Bar, BarWidget, Context};
+use crate::{block::Block, icons, process};
+
+/// Shown when ibus doesn't report an engine
+const PLACEHOLDER: &str = "-";
+
+/// Shows the active IBus keyboard layout, and cycles through the configured engines on click
+pub struct Keyboard {
+	block: Block,
+	/// The engines switched between, in order
+	engines: Vec<String>,
+}
+
+impl Keyboard {
+	pub fn new(cx: &Context) -> Self {
+		Keyboard { block: cx.block.clone(), engines: cx.config.keyboard.engines.clone() }
+	}
+
+	fn render(&self) -> Format {
+		let output = match process::output(Command::new("ibus").arg("engine")) {
+			Ok(out) => out,
+			_ => return bfmt![text[""]],
+		};
+		let string = String::from_utf8_lossy(&output.stdout);
+		// ibus prints errors while it's restarting, which aren't worth showing
+		let layout = Some(string.as_ref()).filter(|_| output.status.success()).and_then(layout);
+		let layout = layout.unwrap_or(PLACEHOLDER);
+		self.block.apply(bfmt![
+			click[MouseButton::Left => fn "kb_next"]
+			fmt["{} {}", self.block.icon(icons::KEYBOARD), layout]
+		])
+	}
+}
+
+impl BarWidget for Keyboard {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let engines = self.engines.clone();
+		bar.register_fn("kb_next", move || {
+			report("Couldn't switch keyboard layout", next_engine(&engines))
+		});
+		bar.add(Periodic::new(
+			Duration::from_secs(1),
+			staggered(self.block.schedule(), move || self.render()),
+		));
+	}
+}
+
+/// The layout to show for the output of `ibus engine`, or `None` if it isn't an engine name.
+/// XKB engines are named like `xkb:us::eng`, and are shown by their layout. Other engines, such
+/// as `anthy`, are shown by their name.
+pub fn layout(output: &str) -> Option<&str> {
+	let engine = output.trim();
+	let valid = |c: char| c.is_ascii_alphanumeric() || ":_-.@".contains(c);
+	if engine.is_empty() || !engine.chars().all(valid) {
+		return None;
+	}
+	match engine.strip_prefix("xkb:") {
+		Some(rest) => rest.split(':').next().filter(|layout| !layout.is_empty()),
+		None => Some(engine),
+	}
+}
+
+/// Switches to the engine after the current one in `engines`, or to the first one if the current
+/// engine isn't listed
+pub fn next_engine(engines: &[String]) -> anyhow::Result<()> {
+	if engines.is_empty() {
+		return Ok(());
+	}
+	let output = Command::new("ibus").arg("engine").output()?;
+	let current = String::from_utf8_lossy(&output.stdout);
+	let next = match engines.iter().position(|engine| engine == current.trim()) {
+		Some(i) => &engines[(i + 1) % engines.len()],
+		None => &engines[0],
+	};
+	let status = Command::new("ibus").args(&["engine", next]).status()?;
+	match status.success() {
+		true => Ok(()),
+		false => Err(anyhow!("ibus couldn't switch to {}", next)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn xkb_engines() {
+		assert_eq!(layout("xkb:us::eng\n"), Some("us"));
+		assert_eq!(layout("xkb:de:nodeadkeys:deu\n"), Some("de"));
+		assert_eq!(layout("  xkb:gb:extd:eng  "), Some("gb"));
+	}
+
+	#[test]
+	fn other_engines() {
+		assert_eq!(layout("anthy\n"), Some("anthy"));
+		assert_eq!(layout("mozc-jp\n"), Some("mozc-jp"));
+		assert_eq!(layout("table:wubi-jidian86"), Some("table:wubi-jidian86"));
+	}
+
+	#[test]
+	fn empty() {
+		assert_eq!(layout(""), None);
+		assert_eq!(layout("\n"), None);
+		assert_eq!(layout("xkb:"), None);
+		assert_eq!(layout("xkb::eng"), None);
+	}
+
+	#[test]
+	fn malformed() {
+		assert_eq!(layout("Failed to get global engine.\n"), None);
+		assert_eq!(layout("No engine is set.\n"), None);
+		assert_eq!(layout("xkb:us::eng\nxkb:de::ger\n"), None);
+		assert_eq!(layout("(ibus:1234): IBUS-WARNING"), None);
+	}
+}
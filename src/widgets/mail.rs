@@ -0,0 +1,68 @@
+use std::{fs, path::PathBuf};
+
+use unixbar::{bfmt, format::Format, widget::Periodic, Duration};
+
+use super::{catch, staggered, Bar, BarWidget, Context};
+use crate::{block::Block, config::MailConfig, icons, template};
+
+/// Shows the number of unread mails
+pub struct Mail {
+	config: MailConfig,
+	block: Block,
+	source: Box<dyn MailSource + Send + Sync>,
+}
+
+impl Mail {
+	pub fn new(cx: &Context) -> Self {
+		let config = cx.config.mail.clone();
+		let source = Box::new(Maildirs(config.maildirs.clone()));
+		Mail { config, block: cx.block.clone(), source }
+	}
+
+	fn render(&self) -> anyhow::Result<Format> {
+		let unread = self.source.unread()?;
+		if unread == 0 && self.config.hide_empty {
+			return Ok(bfmt![text[""]]);
+		}
+		let text = template::fill(
+			&self.config.format,
+			&[("icon", &self.block.icon(icons::MAIL)), ("value", &unread)],
+		);
+		Ok(self.block.apply(bfmt![fmt["{}", text]]))
+	}
+}
+
+impl BarWidget for Mail {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		bar.add(Periodic::new(
+			Duration::from_secs(10),
+			staggered(self.block.schedule(), move || catch(&self.block, || self.render())),
+		));
+	}
+}
+
+/// A source of unread mail counts
+pub trait MailSource {
+	fn unread(&self) -> anyhow::Result<usize>;
+}
+
+/// Counts the new mails in maildirs, which are the files in their `new` directories
+pub struct Maildirs(pub Vec<PathBuf>);
+
+impl MailSource for Maildirs {
+	fn unread(&self) -> anyhow::Result<usize> {
+		let mut count = 0;
+		for maildir in &self.0 {
+			for entry in fs::read_dir(maildir.join("new"))? {
+				let entry = entry?;
+				// Skip hidden files, which mail clients may leave behind
+				if entry.file_type()?.is_file()
+					&& !entry.file_name().to_string_lossy().starts_with('.')
+				{
+					count += 1;
+				}
+			}
+		}
+		Ok(count)
+	}
+}
@@ -0,0 +1,66 @@
+use pulsectl::controllers::{DeviceControl, SourceController};
+use unixbar::{
+	bfmt,
+	format::{ClickAction, Format, MouseButton},
+	widget::Periodic,
+	Duration,
+};
+
+use super::{catch, detach, staggered, truncate, Bar, BarWidget, Context};
+use crate::{
+	block::Block,
+	config::{MicrophoneConfig, VolumeBackend},
+	icons, volume,
+};
+
+/// Shows the name of the audio input device, and lets the user choose another
+pub struct Microphone {
+	config: MicrophoneConfig,
+	block: Block,
+	device_menu: volume::DeviceMenu,
+}
+
+impl Microphone {
+	pub fn new(cx: &Context) -> Self {
+		Microphone {
+			config: cx.config.microphone.clone(),
+			block: cx.block.clone(),
+			device_menu: volume::DeviceMenu::new(&cx.config.device_menu),
+		}
+	}
+
+	fn render(&self) -> anyhow::Result<Format> {
+		let device = SourceController::create()?.get_default_device()?;
+		let (name, description) = (device.name.unwrap_or_default(), device.description);
+		let name = self.device_menu.label(&name, &description.unwrap_or_default());
+		Ok(self.block.apply(bfmt![
+			click[MouseButton::Left => fn "mic_menu"]
+			fg["#9090ff"]
+			fmt["{} {}", self.block.icon(icons::MICROPHONE), truncate(&name, self.config.max_length)]
+		]))
+	}
+}
+
+impl BarWidget for Microphone {
+	fn visible(&self) -> bool {
+		match VolumeBackend::Pulse.probe() {
+			Ok(()) => true,
+			Err(e) => {
+				eprintln!("PulseAudio isn't available: {}", e);
+				false
+			}
+		}
+	}
+
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let device_menu = self.device_menu.clone();
+		bar.register_fn("mic_menu", move || {
+			let device_menu = device_menu.clone();
+			detach("Couldn't change microphone", move || volume::mic_menu(&device_menu))
+		});
+		bar.add(Periodic::new(
+			Duration::from_secs(1),
+			staggered(self.block.schedule(), move || catch(&self.block, || self.render())),
+		));
+	}
+}
@@ -0,0 +1,220 @@
+//! The widgets making up the bar. Each widget registers the functions its click actions call,
+//! and adds itself to the bar.
+
+pub mod battery;
+pub mod brightness;
+pub mod buttons;
+pub mod clock;
+pub mod flair;
+pub mod keyboard;
+pub mod mail;
+#[cfg(feature = "pulse")]
+pub mod microphone;
+pub mod monitor;
+#[cfg(feature = "music")]
+pub mod music;
+pub mod network;
+pub mod nightlight;
+#[cfg(feature = "pulse")]
+pub mod output;
+pub mod power;
+pub mod recording;
+pub mod sparkline;
+pub mod stalled;
+pub mod sun;
+pub mod system;
+pub mod tasks;
+pub mod traffic;
+pub mod updates;
+#[cfg(any(feature = "alsa", feature = "pulse"))]
+pub mod volume;
+pub mod window;
+pub mod workspaces;
+
+use std::{
+	sync::{
+		atomic::{AtomicBool, AtomicUsize, Ordering},
+		Arc,
+	},
+	thread,
+};
+
+use unixbar::{
+	bfmt,
+	format::{ClickAction, Format, I3BarFormatter, MouseButton},
+	UnixBar,
+};
+
+use crate::{
+	block::{Block, Outage, Schedule},
+	config::Config,
+	events::Events,
+	hardware::Hardware,
+	icons,
+	notify::Notification,
+	sampler::Sampler,
+};
+
+#[cfg(feature = "music")]
+pub use self::music::Music;
+#[cfg(any(feature = "alsa", feature = "pulse"))]
+pub use self::volume::Volume;
+pub use self::{
+	battery::Battery,
+	brightness::Brightness,
+	buttons::Buttons,
+	clock::Clock,
+	flair::Flair,
+	keyboard::Keyboard,
+	mail::Mail,
+	monitor::Monitor,
+	network::Network,
+	nightlight::NightLight,
+	power::Power,
+	recording::Recording,
+	sparkline::Sparkline,
+	stalled::Stalled,
+	sun::Sun,
+	system::{Disk, Load, Memory, Temperature},
+	tasks::Tasks,
+	traffic::Traffic,
+	updates::Updates,
+	window::Window,
+	workspaces::Workspaces,
+};
+#[cfg(feature = "pulse")]
+pub use self::{microphone::Microphone, output::Output};
+
+/// The bar widgets are added to
+pub type Bar = UnixBar<I3BarFormatter>;
+
+/// Whether widgets are collapsed to their icons, toggled at runtime
+pub type Compact = Arc<AtomicBool>;
+
+/// The icon shown in front of widgets whose scroll actions are `locked`, followed by a space.
+/// Empty while unlocked.
+pub fn lock_icon(block: &Block, locked: &AtomicBool) -> String {
+	match locked.load(Ordering::Relaxed) {
+		true => format!("{} ", block.icon(icons::LOCK)),
+		false => String::new(),
+	}
+}
+
+/// What widgets are constructed from
+pub struct Context<'a> {
+	pub config: &'a Config,
+	/// The stream of events for external tools
+	pub events: &'a Events,
+	/// The block settings and layout slot of the widget being constructed
+	pub block: &'a Block,
+	/// The system metrics shared by the system widgets
+	pub sampler: &'a Sampler,
+	/// The hardware detected at startup
+	pub hardware: Hardware,
+}
+
+/// A widget that can be added to the bar
+pub trait BarWidget {
+	/// Registers the functions used by the widget's click actions, and adds it to `bar`
+	fn attach(self: Box<Self>, bar: &mut Bar);
+
+	/// Whether the widget has anything to show on this machine. Widgets that don't are left out
+	/// of the bar, unless their block settings say otherwise.
+	fn visible(&self) -> bool {
+		true
+	}
+}
+
+/// Runs the periodic updates of a widget on `schedule`, delaying the first one by its phase and
+/// reporting them to the watchdog. `Periodic` renders once when it's created and then on its own
+/// thread, so sleeping before the second render shifts all later updates by the phase.
+pub fn staggered<F: Fn() -> Format>(schedule: Schedule, render: F) -> impl Fn() -> Format {
+	let renders = AtomicUsize::new(0);
+	move || {
+		if renders.load(Ordering::Relaxed) < 2 && renders.fetch_add(1, Ordering::Relaxed) == 1 {
+			thread::sleep(schedule.phase);
+		}
+		schedule.heartbeat.beat(&render)
+	}
+}
+
+/// Shows a notification with details that don't fit in the bar
+pub fn details(summary: &str, body: &str) -> anyhow::Result<()> {
+	Notification::new(summary, Some(body), Some("dialog-information")).show()?;
+	Ok(())
+}
+
+/// Shows a notification if an action run from a click failed
+pub fn report(summary: &str, result: anyhow::Result<()>) {
+	if let Err(e) = result {
+		eprintln!("{}: {}", summary, e);
+		let body = e.to_string();
+		if let Err(e) = Notification::new(summary, Some(body.as_str()), Some("dialog-error")).show()
+		{
+			eprintln!("Failed to show notification: {}", e);
+		}
+	}
+}
+
+/// Runs a click action on a separate thread, so the bar keeps updating while it waits for the
+/// user, and reports its errors
+pub fn detach<F>(summary: &'static str, action: F)
+where
+	F: FnOnce() -> anyhow::Result<()> + Send + 'static,
+{
+	thread::spawn(move || report(summary, action()));
+}
+
+/// Runs a fallible update of the widget in `block`. If it keeps failing, an error icon takes the
+/// widget's place, so the bar keeps its layout, and clicking it shows the message. Brief
+/// failures only show the placeholder.
+pub fn catch<F: FnOnce() -> anyhow::Result<Format>>(block: &Block, update: F) -> Format {
+	match update() {
+		Ok(format) => {
+			block.recover();
+			format
+		}
+		Err(e) => {
+			let message = e.to_string();
+			match block.fail(message.clone()) {
+				Outage::Brief => return block.apply(bfmt![fmt["{}", block.placeholder()]]),
+				Outage::New => {
+					eprintln!("The {} widget failed to update: {}", block.name(), message)
+				}
+				Outage::Ongoing => (),
+			}
+			let format = bfmt![fg["#ff5555"] fmt["{}", block.icon(icons::ERROR)]];
+			block.apply(clickable(format, &[(MouseButton::Left, &block.error_fn())]))
+		}
+	}
+}
+
+/// Shows why the last update of the widget in `block` failed, if it did
+pub fn show_error(block: &Block) {
+	if let Some(error) = block.error() {
+		let summary = format!("The {} widget failed to update", block.name());
+		let notif = Notification::new(&summary, Some(error.as_str()), Some("dialog-error"));
+		if let Err(e) = notif.show() {
+			eprintln!("Failed to show notification: {}", e);
+		}
+	}
+}
+
+/// Makes `format` call the functions registered as the given names when clicked with their
+/// buttons
+pub fn clickable(format: Format, actions: &[(MouseButton, &str)]) -> Format {
+	actions.iter().fold(format, |format, (button, name)| {
+		let action = ClickAction::Function(button.clone(), String::from(*name));
+		Format::Clickable(action, Box::new(format))
+	})
+}
+
+/// Shortens `text` to at most `max` characters, marking where it was cut with an ellipsis
+pub fn truncate(text: &str, max: usize) -> String {
+	if text.chars().count() <= max {
+		return text.to_owned();
+	}
+	let mut short: String = text.chars().take(max.saturating_sub(1)).collect();
+	short.push('…');
+	short
+}
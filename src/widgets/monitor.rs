@@ -0,0 +1,204 @@
+use std::{
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc, Mutex,
+	},
+	time::Duration as StdDuration,
+};
+
+use anyhow::anyhow;
+use unixbar::{
+	bfmt,
+	format::{Format, MouseButton},
+	widget::Periodic,
+	Duration,
+};
+
+use super::{catch, clickable, detach, staggered, Bar, BarWidget, Context};
+use crate::{
+	block::Block,
+	cache::Cached,
+	config::MonitorConfig,
+	ddc::{self, Display, Level},
+	icons,
+	osd::Osd,
+	template,
+};
+
+/// The brightness last read, with the index of the display it was read from
+type Reading = (usize, Result<Level, String>);
+
+/// The displays ddcutil found, and the one controlled
+#[derive(Clone, Default)]
+struct Displays {
+	/// `None` until ddcutil has been asked
+	found: Arc<Mutex<Option<Arc<Vec<Display>>>>>,
+	/// The index of the display controlled
+	selected: Arc<AtomicUsize>,
+}
+
+impl Displays {
+	/// Asks ddcutil for the displays on the first call, selecting the one numbered `wanted`.
+	/// Detection is slow, so this is only called from the background worker.
+	fn detect(&self, wanted: Option<u32>) -> Arc<Vec<Display>> {
+		if let Some(displays) = &*self.found.lock().unwrap() {
+			return displays.clone();
+		}
+		let displays = ddc::detect();
+		let selected = match wanted {
+			Some(number) => displays.iter().position(|display| display.number == number),
+			None => Some(0),
+		};
+		if selected.is_none() && !displays.is_empty() {
+			eprintln!("warning: ddcutil found no display {:?}, using the first one", wanted);
+		}
+		self.selected.store(selected.unwrap_or(0), Ordering::Relaxed);
+		let displays = Arc::new(displays);
+		*self.found.lock().unwrap() = Some(displays.clone());
+		displays
+	}
+
+	/// The displays found, or `None` while they're being detected and if there are none
+	fn found(&self) -> Option<Arc<Vec<Display>>> {
+		self.found.lock().unwrap().clone().filter(|displays| !displays.is_empty())
+	}
+}
+
+/// Shows the brightness of an external monitor, and adjusts it on scroll over DDC/CI. Hidden
+/// until ddcutil has detected the displays, and when it finds none supporting DDC/CI.
+pub struct Monitor {
+	config: MonitorConfig,
+	block: Block,
+	displays: Displays,
+	osd: Osd,
+}
+
+impl Monitor {
+	pub fn new(cx: &Context) -> Self {
+		Monitor {
+			config: cx.config.monitor.clone(),
+			block: cx.block.clone(),
+			displays: Displays::default(),
+			osd: Osd::new(&cx.config.osd, "Monitor brightness", "display-brightness"),
+		}
+	}
+
+	fn render(&self, level: &Cached<Option<Reading>>) -> anyhow::Result<Format> {
+		let displays = match self.displays.found() {
+			Some(displays) => displays,
+			None => return Ok(bfmt![text[""]]),
+		};
+		let selected = self.displays.selected.load(Ordering::Relaxed);
+		let level = match level.get().flatten() {
+			Some((index, Ok(level))) if index == selected => level,
+			Some((index, Err(e))) if index == selected => return Err(anyhow!(e)),
+			// Not read yet, or read from the display selected before
+			_ => return Ok(bfmt![text[""]]),
+		};
+		let display = &displays[selected];
+		let text = template::fill(
+			&self.config.format,
+			&[
+				("icon", &self.block.icon(icons::MONITOR)),
+				("value", &level.percent()),
+				("display", &display.name),
+			],
+		);
+		let format = bfmt![fg[self.config.color.as_str()] fmt["{}", text]];
+		Ok(self.block.apply(clickable(
+			format,
+			&[
+				(MouseButton::ScrollUp, "monitor_up"),
+				(MouseButton::ScrollDown, "monitor_down"),
+				(MouseButton::Left, "monitor_next"),
+			],
+		)))
+	}
+}
+
+impl BarWidget for Monitor {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let (displays, wanted) = (self.displays.clone(), self.config.display);
+		let interval = StdDuration::from_secs(self.config.interval_secs);
+		let level = Cached::spawn(interval, &self.block.schedule().heartbeat, move || {
+			let found = displays.detect(wanted);
+			let index = displays.selected.load(Ordering::Relaxed);
+			let display = found.get(index)?;
+			Some((index, ddc::get(display.number).map_err(|e| e.to_string())))
+		});
+		let control = Control {
+			displays: self.displays.clone(),
+			level: level.clone(),
+			osd: self.osd.clone(),
+			busy: Arc::default(),
+		};
+		let step = self.config.step as i32;
+		let (up, down, next) = (control.clone(), control.clone(), control);
+		bar.register_fn("monitor_up", move || {
+			let up = up.clone();
+			detach("Couldn't change the monitor brightness", move || up.adjust(step));
+		})
+		.register_fn("monitor_down", move || {
+			let down = down.clone();
+			detach("Couldn't change the monitor brightness", move || down.adjust(-step));
+		})
+		.register_fn("monitor_next", move || {
+			let next = next.clone();
+			detach("Couldn't switch the monitor", move || next.next());
+		});
+		bar.add(Periodic::new(
+			Duration::from_secs(1),
+			staggered(self.block.schedule(), move || catch(&self.block, || self.render(&level))),
+		));
+	}
+}
+
+/// Changes the selected display and its brightness from click actions
+#[derive(Clone)]
+struct Control {
+	displays: Displays,
+	level: Cached<Option<Reading>>,
+	osd: Osd,
+	/// Held while ddcutil runs, so quick scrolls are applied one after another
+	busy: Arc<Mutex<()>>,
+}
+
+impl Control {
+	/// Changes the brightness of the selected display by `diff` percent. Does nothing until a
+	/// display has been detected.
+	fn adjust(&self, diff: i32) -> anyhow::Result<()> {
+		let _busy = self.busy.lock().unwrap();
+		let displays = match self.displays.found() {
+			Some(displays) => displays,
+			None => return Ok(()),
+		};
+		let index = self.displays.selected.load(Ordering::Relaxed);
+		let number = displays[index].number;
+		let level = match self.level.get().flatten() {
+			Some((read, Ok(level))) if read == index => level,
+			_ => ddc::get(number)?,
+		};
+		let max = level.max as i32;
+		let current = (level.current as i32 + diff * max / 100).clamp(0, max) as u32;
+		ddc::set(number, current)?;
+		let level = Level { current, ..level };
+		self.level.set(Some((index, Ok(level))));
+		self.osd.show(level.percent() as u8);
+		Ok(())
+	}
+
+	/// Switches to the next display, and reads its brightness
+	fn next(&self) -> anyhow::Result<()> {
+		let _busy = self.busy.lock().unwrap();
+		let displays = match self.displays.found() {
+			Some(displays) => displays,
+			None => return Ok(()),
+		};
+		let selected = &self.displays.selected;
+		let index = (selected.load(Ordering::Relaxed) + 1) % displays.len();
+		selected.store(index, Ordering::Relaxed);
+		let level = ddc::get(displays[index].number);
+		self.level.set(Some((index, level.map_err(|e| e.to_string()))));
+		Ok(())
+	}
+}
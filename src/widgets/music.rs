@@ -0,0 +1,188 @@
+use anyhow::anyhow;
+use unixbar::{
+	bfmt,
+	format::{ClickAction, Format, MouseButton},
+	widget::Periodic,
+	Duration,
+};
+
+use super::{catch, clickable, detach, staggered, Bar, BarWidget, Context};
+use crate::{
+	art::Accents,
+	block::Block,
+	clipboard,
+	color::Color,
+	config::{ClipboardTool, MusicBackend, MusicConfig},
+	icons,
+	mpris::{self, Player, Selection, Song},
+	template,
+};
+
+/// Stands in for the playback icon while laying out the song
+const ICON_MARK: char = '\u{0}';
+
+/// The click actions of the song
+const CONTROLS: &[(MouseButton, &str)] = &[
+	(MouseButton::Left, "mus_prev"),
+	(MouseButton::Middle, "mus_toggle"),
+	(MouseButton::Right, "mus_next"),
+];
+
+/// The click actions of the playback icon when `copy` is set, where a left click copies the song
+/// instead
+const ICON_CONTROLS: &[(MouseButton, &str)] = &[
+	(MouseButton::Left, "mus_copy"),
+	(MouseButton::Middle, "mus_toggle"),
+	(MouseButton::Right, "mus_next"),
+];
+
+/// Shows the song playing in a media player, and controls playback
+pub struct Music {
+	config: MusicConfig,
+	block: Block,
+	/// The media player controlled by the widget
+	player: Selection,
+	/// The album art colors, when the color is derived from the art
+	accents: Option<Accents>,
+	clipboard: Option<ClipboardTool>,
+}
+
+impl Music {
+	pub fn new(cx: &Context) -> Self {
+		Music {
+			config: cx.config.music.clone(),
+			block: cx.block.clone(),
+			player: Selection::default(),
+			clipboard: cx.config.clipboard,
+			accents: match cx.config.music.album_accent {
+				true => Accents::new(),
+				false => None,
+			},
+		}
+	}
+
+	fn render(&self) -> anyhow::Result<Format> {
+		let players = Player::list(self.config.backend)?;
+		let song = match mpris::resolve(&self.player, &players) {
+			Some(current) => current.song()?.map(|song| (current, song)),
+			None => None,
+		};
+		// Playing or paused
+		let (current, song) = match song {
+			Some(song) => song,
+			None => return Ok(bfmt![text[""]]),
+		};
+		let icon = match song.playing {
+			true => self.block.icon(icons::PLAYING),
+			false => self.block.icon(icons::PAUSED),
+		};
+		let layout = match self.config.short {
+			true => &self.config.short_format,
+			false => &self.config.format,
+		};
+		// Mark where the icon goes, so it can be given its own click actions
+		let full = template::render(layout, |name| match name {
+			"icon" => Some(String::from(ICON_MARK)),
+			name => field(&song, name),
+		});
+		let color = self.color(&song);
+		let color = color.as_str();
+		let icon_controls = match self.config.copy {
+			true => ICON_CONTROLS,
+			false => CONTROLS,
+		};
+		let playback = || clickable(bfmt![fg[color] fmt["{}", icon]], icon_controls);
+		let marks = full.matches(ICON_MARK).count();
+		let width = full.chars().count() - marks + marks * icon.chars().count();
+		let mut parts = match self.block.compact(width, icon.chars().count()) {
+			true => vec![playback()],
+			false => {
+				let mut parts = Vec::new();
+				for (i, text) in full.split(ICON_MARK).enumerate() {
+					if i > 0 {
+						parts.push(playback());
+					}
+					if !text.is_empty() {
+						parts.push(clickable(bfmt![fg[color] fmt["{}", text]], CONTROLS));
+					}
+				}
+				parts
+			}
+		};
+		// Only offer choosing a player when there's more than one
+		if players.len() > 1 {
+			let label = bfmt![
+				click[MouseButton::Left => fn "mus_choose"]
+				fg[color]
+				fmt["[{}] ", current.identity()]
+			];
+			parts.insert(0, label);
+		}
+		Ok(self.block.apply(Format::Concat(parts.into_iter().map(Box::new).collect())))
+	}
+
+	/// The color of the widget for `song`, from its album art if enabled
+	fn color(&self, song: &Song) -> Color {
+		self.accents
+			.as_ref()
+			.zip(song.art_url.as_deref())
+			.and_then(|(accents, url)| accents.get(url))
+			.unwrap_or_else(|| self.config.color.clone())
+	}
+}
+
+impl BarWidget for Music {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let backend = self.config.backend;
+		// Media play funtions
+		for &(name, method) in
+			&[("mus_toggle", "PlayPause"), ("mus_prev", "Previous"), ("mus_next", "Next")]
+		{
+			let player = self.player.clone();
+			bar.register_fn(name, move || mpris::control(backend, &player, method).unwrap_or(()));
+		}
+		let (player, tool) = (self.player.clone(), self.clipboard);
+		let template = self.config.copy_format.clone();
+		bar.register_fn("mus_copy", move || {
+			let (player, template) = (player.clone(), template.clone());
+			detach("Couldn't copy the song", move || share(backend, &player, &template, tool))
+		});
+		let player = self.player.clone();
+		bar.register_fn("mus_choose", move || {
+			let player = player.clone();
+			detach("Couldn't change media player", move || mpris::choose(backend, &player))
+		});
+		bar.add(Periodic::new(
+			Duration::from_secs(1),
+			staggered(self.block.schedule(), move || catch(&self.block, || self.render())),
+		));
+	}
+}
+
+/// The value of the placeholder `name` for `song`
+fn field(song: &Song, name: &str) -> Option<String> {
+	match name {
+		"artist" => Some(song.artist.clone()),
+		"album" => Some(song.album.clone()),
+		"title" => Some(song.title.clone()),
+		"track" => song.track.map(|track| track.to_string()),
+		"rate" => Some(song.rate.to_string()),
+		_ => None,
+	}
+}
+
+/// Copies the song playing in the selected player to the clipboard, laid out by `template`
+fn share(
+	backend: MusicBackend,
+	player: &Selection,
+	template: &str,
+	tool: Option<ClipboardTool>,
+) -> anyhow::Result<()> {
+	let players = Player::list(backend)?;
+	let song = match mpris::resolve(player, &players) {
+		Some(current) => current.song()?,
+		None => None,
+	};
+	let song = song.ok_or_else(|| anyhow!("Nothing is playing"))?;
+	clipboard::copy(tool, &template::render(template, |name| field(&song, name)))
+}
@@ -0,0 +1,433 @@
+use std::{
+	net::IpAddr,
+	process::Command,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc, Mutex,
+	},
+	time::Instant,
+};
+
+use anyhow::anyhow;
+use systemstat::{Platform, System};
+use unixbar::{
+	bfmt,
+	format::{Format, MouseButton},
+	widget::Periodic,
+	Duration,
+};
+
+use super::{catch, clickable, detach, details, report, staggered, Bar, BarWidget, Context};
+use crate::{
+	block::{self, Block},
+	clipboard,
+	config::{ClipboardTool, NetworkConfig, PortalOpen, Urgency},
+	events::{Event, Events},
+	icons, process,
+	threshold::{Alert, Direction, ThresholdNotifier},
+	units,
+};
+
+/// The click actions of the widget
+const CONTROLS: &[(MouseButton, &str)] = &[
+	(MouseButton::Left, "network_expand"),
+	(MouseButton::Middle, "network_copy"),
+	(MouseButton::Right, "network_details"),
+];
+
+/// The internet access of the active connection, as checked by NetworkManager
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Connectivity {
+	Full,
+	/// Behind a captive portal, e.g. on public wifi before logging in
+	Portal,
+	/// Connected, but without internet access
+	Limited,
+	/// Not connected to any network
+	Offline,
+	/// The check is disabled or failed
+	Unknown,
+}
+
+impl Connectivity {
+	/// Parses the output of `nmcli networking connectivity check`
+	fn parse(state: &str) -> Self {
+		match state.trim_end() {
+			"full" => Connectivity::Full,
+			"portal" => Connectivity::Portal,
+			"limited" => Connectivity::Limited,
+			"none" => Connectivity::Offline,
+			_ => Connectivity::Unknown,
+		}
+	}
+}
+
+/// Shows the active connection and whether it has internet access
+pub struct Network {
+	config: NetworkConfig,
+	block: Block,
+	events: Events,
+	throughput: Throughput,
+	/// The name of the last seen connection, to report changes
+	connection: Mutex<Option<String>>,
+	/// Whether all details are shown inline, toggled by clicking the widget
+	expanded: Arc<AtomicBool>,
+	/// Whether the last update found a captive portal
+	portal: AtomicBool,
+	clipboard: Option<ClipboardTool>,
+	/// Notifies of sustained heavy traffic on metered connections, if enabled
+	metered_alert: Option<ThresholdNotifier>,
+	/// Since when the traffic has been above `metered_alert`
+	heavy_since: Mutex<Option<Instant>>,
+}
+
+impl Network {
+	pub fn new(cx: &Context) -> Self {
+		let config = &cx.config.network;
+		if let Some(iface) = &config.interface {
+			check_interface(iface);
+		}
+		Network {
+			config: config.clone(),
+			block: cx.block.clone(),
+			events: cx.events.clone(),
+			throughput: Throughput::default(),
+			connection: Mutex::new(None),
+			expanded: Arc::new(AtomicBool::new(false)),
+			portal: AtomicBool::new(false),
+			clipboard: cx.config.clipboard,
+			metered_alert: config.metered_alert.map(|threshold| {
+				let body = format!(
+					"Over {} KiB/s for {} seconds on a metered connection",
+					threshold, config.metered_alert_secs
+				);
+				ThresholdNotifier::new(
+					Direction::Above,
+					threshold,
+					0.0,
+					Alert {
+						summary: String::from("Heavy network traffic"),
+						body,
+						icon: String::from("network-transmit-receive"),
+						urgency: Urgency::Normal,
+						value_hint: false,
+					},
+				)
+			}),
+			heavy_since: Mutex::new(None),
+		}
+	}
+
+	/// Notifies once the combined `rates` in bytes per second have been above the alert threshold
+	/// for `metered_alert_secs`. Rates are `None` when the connection isn't metered.
+	fn watch_metered(&self, rates: Option<(f64, f64)>) -> anyhow::Result<()> {
+		let (alert, threshold) = match (&self.metered_alert, self.config.metered_alert) {
+			(Some(alert), Some(threshold)) => (alert, threshold),
+			_ => return Ok(()),
+		};
+		let rate = rates.map_or(0.0, |(rx, tx)| (rx + tx) / 1024.0);
+		let mut heavy_since = self.heavy_since.lock().unwrap();
+		let heavy = rate > threshold;
+		match heavy {
+			true => {
+				heavy_since.get_or_insert_with(Instant::now);
+			}
+			false => *heavy_since = None,
+		}
+		let secs = self.config.metered_alert_secs;
+		let sustained = heavy_since.map_or(false, |since| since.elapsed().as_secs() >= secs);
+		if alert.latch(sustained, !heavy) {
+			alert.notify(rate)?;
+		}
+		Ok(())
+	}
+
+	fn render(&self) -> anyhow::Result<Format> {
+		let connection = nmcli(&["--terse", "connection", "show", "--active"]);
+		let connectivity = nmcli(&["networking", "connectivity", "check"]);
+		let icon = connection
+			.split(':')
+			.nth(2)
+			.map(|kind| match kind {
+				"802-3-ethernet" => icons::ETHERNET,
+				"802-11-wireless" => icons::WIRELESS,
+				_ => icons::NETWORK_UNKNOWN,
+			})
+			.unwrap_or(icons::NETWORK_UNKNOWN);
+		let icon = self.block.icon(icon);
+		let (name, color) = match connection
+			.split(':')
+			.next()
+			.ok_or_else(|| anyhow!("nmcli returned unexpected data!"))?
+		{
+			"" => ("Disconnected", self.config.disconnected_color.as_str()),
+			name => (name, self.config.connected_color.as_str()),
+		};
+		let connectivity = Connectivity::parse(&connectivity);
+		let config = &self.config;
+		let (status, status_color) = match connectivity {
+			Connectivity::Full => (String::new(), color),
+			Connectivity::Portal => {
+				(format!(" {}", self.block.icon(icons::PORTAL)), config.portal_color.as_str())
+			}
+			Connectivity::Limited => (String::from("!"), config.limited_color.as_str()),
+			Connectivity::Offline => {
+				(format!(" {}", self.block.icon(icons::OFFLINE)), config.offline_color.as_str())
+			}
+			Connectivity::Unknown => (String::from("?"), config.unknown_color.as_str()),
+		};
+		// The status marker is colored separately, to stand out on a connected network
+		let colored = |head: &str, tail: &str| {
+			let parts = vec![
+				Box::new(bfmt![fg[color] fmt["{}", head]]),
+				Box::new(bfmt![fg[status_color] fmt["{}", status]]),
+				Box::new(bfmt![fg[color] fmt["{}", tail]]),
+			];
+			Format::Concat(parts)
+		};
+		// Report connection changes
+		{
+			let mut last = self.connection.lock().unwrap();
+			if last.as_deref() != Some(name) {
+				*last = Some(name.to_owned());
+				self.events.emit(Event::ConnectionChanged { connection: name });
+			}
+		}
+		let device = connection.lines().next().unwrap_or("").split(':').nth(3);
+		let device = self.config.interface.as_deref().or(device).filter(|iface| !iface.is_empty());
+		// Addresses of the interface
+		let addresses: String =
+			[(self.config.ipv4, "IP4.ADDRESS"), (self.config.ipv6, "IP6.ADDRESS")]
+				.iter()
+				.filter(|(enabled, _)| *enabled)
+				.filter_map(|(_, field)| address(device?, field))
+				.map(|address| format!(" {}", address))
+				.collect();
+		// NetworkManager is only asked when the icon or the alert needs it
+		let wanted = self.config.metered || self.metered_alert.is_some();
+		let metered = wanted && device.map_or(false, is_metered);
+		let metered_icon = match metered && self.config.metered {
+			true => format!(" {}", self.block.icon(icons::METERED)),
+			false => String::new(),
+		};
+		// Download and upload rates, also sampled to watch the traffic on metered connections
+		let watched = metered && self.metered_alert.is_some();
+		let rates = match device {
+			Some(iface) if self.config.throughput || watched => self.throughput.sample(iface)?,
+			_ => None,
+		};
+		self.watch_metered(rates.filter(|_| metered))?;
+		let rates = rates
+			.filter(|_| self.config.throughput)
+			.map(|(rx, tx)| format!(" ↓{}/s ↑{}/s", units::bytes(rx), units::bytes(tx)));
+		let format = if self.expanded.load(Ordering::Relaxed) {
+			let details = device.map(expanded).unwrap_or_default();
+			colored(&format!("{} {}{}", icon, name, metered_icon), &details)
+		} else {
+			let (head, tail) = match self.config.short {
+				true => (format!("{}{}", icon, metered_icon), String::new()),
+				false => (
+					format!("{} {}{}", icon, name, metered_icon),
+					addresses + &rates.unwrap_or_default(),
+				),
+			};
+			let width = head.chars().count() + status.chars().count() + tail.chars().count();
+			match self.block.compact(width, icon.chars().count()) {
+				true => bfmt![fg[color] fmt["{}", icon]],
+				false => colored(&head, &tail),
+			}
+		};
+		let format = clickable(format, CONTROLS);
+		let portal = connectivity == Connectivity::Portal;
+		let open = self.config.portal_open;
+		// Opens the login page once when a portal shows up, not on every update
+		let entered = !self.portal.swap(portal, Ordering::Relaxed) && portal;
+		if entered && open == PortalOpen::Auto {
+			let command = self.config.portal_command.clone();
+			detach("Couldn't open the captive portal", move || {
+				Command::new("sh").args(&["-c", &command]).status()?;
+				Ok(())
+			});
+		}
+		// Behind a captive portal, a left click can open the login page instead
+		let format = match portal && open != PortalOpen::Never {
+			true => block::bind(format, &MouseButton::Left, &self.config.portal_command),
+			false => format,
+		};
+		Ok(self.block.apply(format))
+	}
+}
+
+impl BarWidget for Network {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let expanded = self.expanded.clone();
+		bar.register_fn("network_expand", move || {
+			expanded.fetch_xor(true, Ordering::AcqRel);
+		});
+		let (interface, tool) = (self.config.interface.clone(), self.clipboard);
+		bar.register_fn("network_copy", move || {
+			report("Couldn't copy the address", copy_address(interface.as_deref(), tool))
+		});
+		let interface = self.config.interface.clone();
+		bar.register_fn("network_details", move || {
+			show_details(interface.as_deref()).unwrap_or(())
+		});
+		bar.add(Periodic::new(
+			Duration::from_secs(1),
+			staggered(self.block.schedule(), move || catch(&self.block, || self.render())),
+		));
+	}
+}
+
+/// Runs nmcli, returning its output, or nothing if it failed
+pub fn nmcli(args: &[&str]) -> String {
+	process::output(Command::new("nmcli").args(args))
+		.map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+		.unwrap_or_default()
+}
+
+/// The interface of the active connection, if there is one
+pub fn active_interface() -> Option<String> {
+	let active = nmcli(&["--get-values", "DEVICE", "connection", "show", "--active"]);
+	active.lines().next().filter(|device| !device.is_empty()).map(String::from)
+}
+
+/// Whether NetworkManager considers the connection on `iface` metered, either because it's
+/// configured so or because it guessed so, e.g. for a phone's hotspot
+fn is_metered(iface: &str) -> bool {
+	nmcli(&["--get-values", "GENERAL.METERED", "device", "show", iface]).starts_with("yes")
+}
+
+/// The first address of `iface` that isn't link-local. `field` is the nmcli field listing the
+/// addresses, `IP4.ADDRESS` or `IP6.ADDRESS`.
+fn address(iface: &str, field: &str) -> Option<IpAddr> {
+	let output = nmcli(&["--get-values", field, "device", "show", iface]);
+	output
+		.trim()
+		.split(" | ")
+		// Strip the prefix length
+		.filter_map(|address| address.split('/').next()?.parse().ok())
+		.find(|address| match address {
+			IpAddr::V4(v4) => !v4.is_link_local(),
+			IpAddr::V6(v6) => v6.segments()[0] & 0xffc0 != 0xfe80,
+		})
+}
+
+/// The SSID, address, gateway, DNS servers and signal strength of `iface`, as shown when the
+/// widget is expanded. Wired interfaces have no SSID or signal.
+fn expanded(iface: &str) -> String {
+	let info = nmcli(&["--get-values", "IP4.ADDRESS,IP4.GATEWAY,IP4.DNS", "device", "show", iface]);
+	let mut lines = info.lines();
+	let mut fields = Vec::new();
+	// The access point in use is marked with a star
+	let wifi = nmcli(&[
+		"--terse",
+		"--fields",
+		"IN-USE,SSID,SIGNAL",
+		"device",
+		"wifi",
+		"list",
+		"ifname",
+		iface,
+		"--rescan",
+		"no",
+	]);
+	let access_point = wifi.lines().find_map(|line| line.strip_prefix("*:"));
+	let access_point = access_point.and_then(|line| line.rsplit_once(':'));
+	if let Some((ssid, _)) = access_point {
+		fields.push(ssid.replace("\\:", ":"));
+	}
+	for label in &["IP", "GW", "DNS"] {
+		match lines.next() {
+			Some(value) if !value.is_empty() => fields.push(format!("{} {}", label, value)),
+			_ => (),
+		}
+	}
+	if let Some((_, signal)) = access_point {
+		fields.push(format!("{}%", signal));
+	}
+	fields.iter().map(|field| format!(" {}", field)).collect()
+}
+
+/// Copies the address of `interface`, or of the active connection's interface, to the clipboard
+pub fn copy_address(interface: Option<&str>, tool: Option<ClipboardTool>) -> anyhow::Result<()> {
+	let active = nmcli(&["--get-values", "DEVICE", "connection", "show", "--active"]);
+	let device = interface
+		.or_else(|| active.lines().next())
+		.filter(|device| !device.is_empty())
+		.ok_or_else(|| anyhow!("Not connected"))?;
+	let address = address(device, "IP4.ADDRESS")
+		.or_else(|| address(device, "IP6.ADDRESS"))
+		.ok_or_else(|| anyhow!("{} has no address", device))?;
+	clipboard::copy(tool, &address.to_string())
+}
+
+/// The fields shown by `show_details`, with their labels
+const DETAILS: &[(&str, &str)] = &[
+	("IP4.ADDRESS", "Address"),
+	("IP4.GATEWAY", "Gateway"),
+	("IP4.DNS", "DNS"),
+	("IP6.ADDRESS", "IPv6 address"),
+	("IP6.GATEWAY", "IPv6 gateway"),
+	("IP6.DNS", "IPv6 DNS"),
+];
+
+/// Shows a notification with the addresses, gateways and DNS servers of `interface`, or of the
+/// active connection's interface. Fields the connection doesn't have are left out, so IPv4-only
+/// and IPv6-only setups only show what they use.
+pub fn show_details(interface: Option<&str>) -> anyhow::Result<()> {
+	let active = nmcli(&["--get-values", "DEVICE", "connection", "show", "--active"]);
+	let device = match interface.or_else(|| active.lines().next()) {
+		Some(device) if !device.is_empty() => device,
+		_ => return details("Network", "Disconnected"),
+	};
+	let fields: Vec<&str> = DETAILS.iter().map(|(field, _)| *field).collect();
+	let info = nmcli(&["--get-values", &fields.join(","), "device", "show", device]);
+	let mut body = format!("Interface: {}", device);
+	// nmcli prints a line per field, with multiple values separated by ` | `
+	for ((_, label), value) in DETAILS.iter().zip(info.lines()) {
+		let value = value.replace("\\:", ":").replace(" | ", ", ");
+		if !value.is_empty() {
+			body.push_str(&format!("\n{}: {}", label, value));
+		}
+	}
+	details("Network", &body)
+}
+
+/// Calculates the throughput of a network interface from its byte counters
+#[derive(Default)]
+pub struct Throughput {
+	/// The interface, time and received and transmitted byte counts of the last sample
+	last: Mutex<Option<(String, Instant, u64, u64)>>,
+}
+
+impl Throughput {
+	/// Samples the byte counters of `iface`. Returns the receive and transmit rates in bytes per
+	/// second since the previous sample, or `None` on the first sample of an interface.
+	pub fn sample(&self, iface: &str) -> anyhow::Result<Option<(f64, f64)>> {
+		let stats = System::new().network_stats(iface)?;
+		let now = Instant::now();
+		let (rx, tx) = (stats.rx_bytes.as_u64(), stats.tx_bytes.as_u64());
+		let mut last = self.last.lock().unwrap();
+		let rates = match last.take() {
+			Some((name, then, last_rx, last_tx)) if name == iface => {
+				let secs = now.duration_since(then).as_secs_f64().max(0.001);
+				let rx_rate = rx.saturating_sub(last_rx) as f64 / secs;
+				let tx_rate = tx.saturating_sub(last_tx) as f64 / secs;
+				Some((rx_rate, tx_rate))
+			}
+			_ => None,
+		};
+		*last = Some((iface.to_owned(), now, rx, tx));
+		Ok(rates)
+	}
+}
+
+/// Prints a warning if there's no network interface called `name`
+pub fn check_interface(name: &str) {
+	if let Ok(networks) = System::new().networks() {
+		if !networks.contains_key(name) {
+			eprintln!("warning: configured network interface {} does not exist", name);
+		}
+	}
+}
@@ -0,0 +1,155 @@
+use std::{
+	io::ErrorKind as IoErrorKind,
+	process::Command,
+	sync::{
+		atomic::{AtomicBool, AtomicU32, Ordering},
+		Arc,
+	},
+};
+
+use anyhow::anyhow;
+use unixbar::{bfmt, format::MouseButton, widget::Periodic, Duration};
+
+use super::{clickable, detach, staggered, Bar, BarWidget, Context};
+use crate::{
+	block::Block,
+	config::{NightLightConfig, NightLightTool},
+	icons, template,
+};
+
+/// The color temperatures redshift and gammastep accept, in Kelvin
+const TEMPERATURES: (u32, u32) = (1000, 25000);
+
+impl NightLightTool {
+	pub fn program(self) -> &'static str {
+		match self {
+			NightLightTool::Redshift => "redshift",
+			NightLightTool::Gammastep => "gammastep",
+		}
+	}
+
+	/// Whether the program is installed
+	fn installed(self) -> bool {
+		match Command::new(self.program()).arg("-V").output() {
+			Ok(_) => true,
+			Err(e) => e.kind() != IoErrorKind::NotFound,
+		}
+	}
+
+	/// Sets the color temperature to `kelvin`, or back to neutral when `None`. Both programs
+	/// take the same arguments.
+	fn apply(self, kelvin: Option<u32>) -> anyhow::Result<()> {
+		let mut command = Command::new(self.program());
+		match kelvin {
+			// Replaces the previous adjustment rather than adding to it
+			Some(kelvin) => command.args(&["-P", "-O", &kelvin.to_string()]),
+			None => command.arg("-x"),
+		};
+		let output = command.output()?;
+		match output.status.success() {
+			true => Ok(()),
+			false => Err(anyhow!(
+				"{} failed: {}",
+				self.program(),
+				String::from_utf8_lossy(&output.stderr).trim()
+			)),
+		}
+	}
+}
+
+/// Shows whether the night light is on and its color temperature. Clicking toggles it and
+/// scrolling changes the temperature.
+pub struct NightLight {
+	config: NightLightConfig,
+	block: Block,
+	/// The installed tool, if any
+	tool: Option<NightLightTool>,
+	enabled: Arc<AtomicBool>,
+	/// The color temperature while on, in Kelvin
+	temperature: Arc<AtomicU32>,
+}
+
+impl NightLight {
+	pub fn new(cx: &Context) -> Self {
+		let config = &cx.config.nightlight;
+		let tool = match config.tool {
+			Some(tool) => Some(tool).filter(|tool| tool.installed()),
+			None => [NightLightTool::Gammastep, NightLightTool::Redshift]
+				.iter()
+				.copied()
+				.find(|tool| tool.installed()),
+		};
+		let (min, max) = TEMPERATURES;
+		NightLight {
+			config: config.clone(),
+			block: cx.block.clone(),
+			tool,
+			enabled: Arc::new(AtomicBool::new(config.enabled)),
+			temperature: Arc::new(AtomicU32::new(config.temperature.clamp(min, max))),
+		}
+	}
+}
+
+impl BarWidget for NightLight {
+	/// Hidden when neither redshift nor gammastep is installed
+	fn visible(&self) -> bool {
+		self.tool.is_some()
+	}
+
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let tool = match self.tool {
+			Some(tool) => tool,
+			None => return,
+		};
+		let (enabled, temperature) = (self.enabled.clone(), self.temperature.clone());
+		let apply = move || {
+			let kelvin = Some(temperature.load(Ordering::Relaxed))
+				.filter(|_| enabled.load(Ordering::Relaxed));
+			detach("Couldn't change the night light", move || tool.apply(kelvin));
+		};
+		if self.enabled.load(Ordering::Relaxed) {
+			apply();
+		}
+		let (toggle, up, down) = (apply.clone(), apply.clone(), apply);
+		let enabled = self.enabled.clone();
+		bar.register_fn("nightlight_toggle", move || {
+			enabled.fetch_xor(true, Ordering::Relaxed);
+			toggle();
+		});
+		let (step, (min, max)) = (self.config.step, TEMPERATURES);
+		let (warmer, cooler) = (self.temperature.clone(), self.temperature.clone());
+		let (warm, cool) = (self.enabled.clone(), self.enabled.clone());
+		// Scrolling up warms the colors, lowering the temperature, and turns the light on
+		bar.register_fn("nightlight_up", move || {
+			let kelvin = warmer.load(Ordering::Relaxed).saturating_sub(step).max(min);
+			warmer.store(kelvin, Ordering::Relaxed);
+			warm.store(true, Ordering::Relaxed);
+			up();
+		})
+		.register_fn("nightlight_down", move || {
+			let kelvin = (cooler.load(Ordering::Relaxed) + step).min(max);
+			cooler.store(kelvin, Ordering::Relaxed);
+			cool.store(true, Ordering::Relaxed);
+			down();
+		});
+		bar.add(Periodic::new(
+			Duration::from_secs(1),
+			staggered(self.block.schedule(), move || {
+				let on = self.enabled.load(Ordering::Relaxed);
+				let icon = self.block.icon(if on { icons::NIGHT_LIGHT } else { icons::SUN });
+				let kelvin = self.temperature.load(Ordering::Relaxed);
+				let text =
+					template::fill(&self.config.format, &[("icon", &icon), ("value", &kelvin)]);
+				let color = if on { "#ffaa55" } else { "#cccccc" };
+				self.block.apply(clickable(
+					bfmt![fg[color] fmt["{}", text]],
+					&[
+						(MouseButton::Left, "nightlight_toggle"),
+						(MouseButton::ScrollUp, "nightlight_up"),
+						(MouseButton::ScrollDown, "nightlight_down"),
+					],
+				))
+			}),
+		));
+	}
+}
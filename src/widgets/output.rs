@@ -0,0 +1,66 @@
+use pulsectl::controllers::{DeviceControl, SinkController};
+use unixbar::{
+	bfmt,
+	format::{ClickAction, Format, MouseButton},
+	widget::Periodic,
+	Duration,
+};
+
+use super::{catch, detach, staggered, truncate, Bar, BarWidget, Context};
+use crate::{
+	block::Block,
+	config::{OutputConfig, VolumeBackend},
+	volume,
+};
+
+/// Shows the name of the audio output device, and lets the user choose another
+pub struct Output {
+	config: OutputConfig,
+	block: Block,
+	device_menu: volume::DeviceMenu,
+}
+
+impl Output {
+	pub fn new(cx: &Context) -> Self {
+		Output {
+			config: cx.config.output.clone(),
+			block: cx.block.clone(),
+			device_menu: volume::DeviceMenu::new(&cx.config.device_menu),
+		}
+	}
+
+	fn render(&self) -> anyhow::Result<Format> {
+		let device = SinkController::create()?.get_default_device()?;
+		let (name, description) = (device.name.unwrap_or_default(), device.description);
+		let name = self.device_menu.label(&name, &description.unwrap_or_default());
+		Ok(self.block.apply(bfmt![
+			click[MouseButton::Left => fn "output_menu"]
+			fg["#9090ff"]
+			fmt["{}", truncate(&name, self.config.max_length)]
+		]))
+	}
+}
+
+impl BarWidget for Output {
+	fn visible(&self) -> bool {
+		match VolumeBackend::Pulse.probe() {
+			Ok(()) => true,
+			Err(e) => {
+				eprintln!("PulseAudio isn't available: {}", e);
+				false
+			}
+		}
+	}
+
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let device_menu = self.device_menu.clone();
+		bar.register_fn("output_menu", move || {
+			let device_menu = device_menu.clone();
+			detach("Couldn't change audio device", move || volume::menu(&device_menu))
+		});
+		bar.add(Periodic::new(
+			Duration::from_secs(1),
+			staggered(self.block.schedule(), move || catch(&self.block, || self.render())),
+		));
+	}
+}
@@ -0,0 +1,60 @@
+use std::process::Command;
+
+use unixbar::{bfmt, format::MouseButton, widget::Text};
+
+use super::{clickable, detach, Bar, BarWidget, Context};
+use crate::{
+	block::{self, Block},
+	config::PowerConfig,
+	icons,
+	picker::Picker,
+};
+
+/// A button opening a power menu, either a configured program like wlogout, or a picker
+/// offering to suspend, reboot or power off
+pub struct Power {
+	config: PowerConfig,
+	block: Block,
+}
+
+impl Power {
+	pub fn new(cx: &Context) -> Self {
+		Power { config: cx.config.power.clone(), block: cx.block.clone() }
+	}
+}
+
+impl BarWidget for Power {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let format = bfmt![
+			fg[self.config.color.as_str()]
+			fmt["{}", self.block.icon(icons::POWER)]
+		];
+		let format = match &self.config.command {
+			Some(command) => block::bind(format, &MouseButton::Left, command),
+			None => {
+				let entries = self.config.entries.clone();
+				bar.register_fn("power_menu", move || {
+					let entries = entries.clone();
+					detach("Couldn't open the power menu", move || menu(&entries));
+				});
+				clickable(format, &[(MouseButton::Left, "power_menu")])
+			}
+		};
+		bar.add(Text::new(self.block.apply(format)));
+	}
+}
+
+/// Lets the user pick one of `entries`, given as `(label, command)` pairs, and runs its command
+fn menu(entries: &[(String, String)]) -> anyhow::Result<()> {
+	let choices: Vec<(String, String)> =
+		entries.iter().enumerate().map(|(i, (label, _))| (i.to_string(), label.clone())).collect();
+	let choice = match Picker::detect().pick("Power", &choices)? {
+		Some(choice) => choice,
+		None => return Ok(()),
+	};
+	let command = choice.parse::<usize>().ok().and_then(|i| entries.get(i));
+	if let Some((_, command)) = command {
+		Command::new("sh").args(&["-c", command]).status()?;
+	}
+	Ok(())
+}
@@ -0,0 +1,35 @@
+use unixbar::{bfmt, format::Format, widget::Periodic, Duration};
+
+use super::{staggered, Bar, BarWidget, Context};
+use crate::{block::Block, config::RecordingConfig, icons};
+
+/// Shows a marker while a screen recording is in progress
+pub struct Recording {
+	config: RecordingConfig,
+	block: Block,
+}
+
+impl Recording {
+	pub fn new(cx: &Context) -> Self {
+		Recording { config: cx.config.recording.clone(), block: cx.block.clone() }
+	}
+
+	fn render(&self) -> Format {
+		let format = match &self.config.lock_file {
+			Some(path) if path.exists() => {
+				bfmt![fg[self.config.color.as_str()] fmt["{} REC", self.block.icon(icons::RECORDING)]]
+			}
+			_ => bfmt![text[""]],
+		};
+		self.block.apply(format)
+	}
+}
+
+impl BarWidget for Recording {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		bar.add(Periodic::new(
+			Duration::from_secs(1),
+			staggered(self.block.schedule(), move || self.render()),
+		));
+	}
+}
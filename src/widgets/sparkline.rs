@@ -0,0 +1,140 @@
+use std::{fs, sync::Mutex};
+
+use anyhow::anyhow;
+use unixbar::{bfmt, format::Format, widget::Periodic, Duration};
+
+use super::{
+	catch,
+	network::{nmcli, Throughput},
+	staggered, Bar, BarWidget, Context,
+};
+use crate::{
+	block::Block,
+	config::{SparklineConfig, SparklineSource},
+	sampler::{Metric, Sampler},
+	samples::{MovingAverage, SampleBuffer},
+};
+
+/// The glyphs of a sparkline, from lowest to highest
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Shows the recent history of the CPU usage, memory usage or network throughput as a
+/// sparkline
+pub struct Sparkline {
+	config: SparklineConfig,
+	block: Block,
+	samples: Mutex<SampleBuffer>,
+	average: Mutex<MovingAverage>,
+	sampler: Sampler,
+	cpu: CpuUsage,
+	throughput: Throughput,
+}
+
+impl Sparkline {
+	pub fn new(cx: &Context) -> Self {
+		let config = &cx.config.sparkline;
+		if config.source == SparklineSource::Memory {
+			cx.sampler.watch(Metric::Memory);
+		}
+		Sparkline {
+			config: config.clone(),
+			block: cx.block.clone(),
+			samples: Mutex::new(SampleBuffer::new(config.width)),
+			average: Mutex::new(MovingAverage::new(config.smoothing)),
+			sampler: cx.sampler.clone(),
+			cpu: CpuUsage::default(),
+			throughput: Throughput::default(),
+		}
+	}
+
+	/// Takes a sample from the configured source, or `None` if it has no value yet
+	fn sample(&self) -> anyhow::Result<Option<f64>> {
+		match self.config.source {
+			SparklineSource::Cpu => self.cpu.sample(),
+			SparklineSource::Memory => {
+				let snapshot = self.sampler.get();
+				let memory = snapshot.memory()?;
+				let used = memory.total.as_u64().saturating_sub(memory.free.as_u64());
+				Ok(Some(used as f64 / memory.total.as_u64().max(1) as f64))
+			}
+			SparklineSource::Network => {
+				let active = nmcli(&["--get-values", "DEVICE", "connection", "show", "--active"]);
+				let iface = self.config.interface.as_deref().or_else(|| active.lines().next());
+				match iface {
+					Some(iface) if !iface.is_empty() => {
+						Ok(self.throughput.sample(iface)?.map(|(rx, tx)| rx + tx))
+					}
+					_ => Ok(Some(0.0)),
+				}
+			}
+		}
+	}
+
+	fn render(&self) -> anyhow::Result<Format> {
+		let mut samples = self.samples.lock().unwrap();
+		if let Some(sample) = self.sample()? {
+			samples.push(self.average.lock().unwrap().add(sample));
+		}
+		// Usage has a fixed range, throughput is scaled to the highest recent rate
+		let max = match self.config.source {
+			SparklineSource::Cpu | SparklineSource::Memory => 1.0,
+			SparklineSource::Network => samples.max().unwrap_or(0.0),
+		};
+		let line: String = samples.normalized(0.0, max).map(bar).collect();
+		Ok(self.block.apply(bfmt![
+			fg["#cc9999"]
+			fmt["{}", line]
+		]))
+	}
+}
+
+impl BarWidget for Sparkline {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		bar.add(Periodic::new(
+			Duration::from_secs(1),
+			staggered(self.block.schedule(), move || catch(&self.block, || self.render())),
+		));
+	}
+}
+
+/// The sparkline glyph for a value from 0.0 to 1.0
+pub fn bar(value: f64) -> char {
+	let index = (value * (BARS.len() - 1) as f64).round() as usize;
+	BARS[index.min(BARS.len() - 1)]
+}
+
+/// Calculates the CPU usage from the counters in `/proc/stat`
+#[derive(Default)]
+pub struct CpuUsage {
+	/// The total and idle time of the last sample
+	last: Mutex<Option<(u64, u64)>>,
+}
+
+impl CpuUsage {
+	/// Samples the CPU time counters. Returns the share of time the CPU was busy since the
+	/// previous sample, from 0.0 to 1.0, or `None` on the first sample.
+	pub fn sample(&self) -> anyhow::Result<Option<f64>> {
+		let stat = fs::read_to_string("/proc/stat")?;
+		let times: Vec<u64> = stat
+			.lines()
+			.next()
+			.filter(|line| line.starts_with("cpu "))
+			.ok_or_else(|| anyhow!("/proc/stat has no cpu line"))?
+			.split_whitespace()
+			.skip(1)
+			.filter_map(|time| time.parse().ok())
+			.collect();
+		let total = times.iter().sum();
+		// Idle and waiting for IO
+		let idle = times.iter().skip(3).take(2).sum();
+		let mut last = self.last.lock().unwrap();
+		let usage = match last.replace((total, idle)) {
+			Some((last_total, last_idle)) if total > last_total => {
+				let busy = (total - last_total).saturating_sub(idle.saturating_sub(last_idle));
+				Some(busy as f64 / (total - last_total) as f64)
+			}
+			_ => None,
+		};
+		Ok(usage)
+	}
+}
@@ -0,0 +1,40 @@
+use unixbar::{bfmt, format::Format, widget::Periodic, Duration};
+
+use super::{Bar, BarWidget};
+use crate::{
+	color::Color,
+	config::{IconStyle, WatchdogConfig},
+	icons,
+	watchdog::Watchdog,
+};
+
+/// Warns about widgets that have stopped updating. Added to the bar when the watchdog is enabled.
+pub struct Stalled {
+	watchdog: Watchdog,
+	color: Color,
+	icon: &'static str,
+}
+
+impl Stalled {
+	pub fn new(watchdog: &Watchdog, config: &WatchdogConfig, icons: IconStyle) -> Self {
+		Stalled {
+			watchdog: watchdog.clone(),
+			color: config.color.clone(),
+			icon: icons.pick(icons::STALLED),
+		}
+	}
+
+	fn render(&self) -> Format {
+		let stalled = self.watchdog.stalled();
+		if stalled.is_empty() {
+			return bfmt![text[""]];
+		}
+		bfmt![fg[self.color.as_str()] fmt["{} {} stalled", self.icon, stalled.join(", ")]]
+	}
+}
+
+impl BarWidget for Stalled {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		bar.add(Periodic::new(Duration::from_secs(1), move || self.render()));
+	}
+}
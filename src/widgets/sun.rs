@@ -0,0 +1,59 @@
+use chrono::{Duration as ChronoDuration, Local, Utc};
+use unixbar::{bfmt, format::Format, widget::Periodic, Duration};
+
+use super::{staggered, Bar, BarWidget, Context};
+use crate::{
+	block::Block,
+	config::SunConfig,
+	icons,
+	sun::{self, moon_icon, moon_phase},
+};
+
+/// Shows the next sunrise or sunset, and optionally the moon phase
+pub struct Sun {
+	config: SunConfig,
+	block: Block,
+}
+
+impl Sun {
+	pub fn new(cx: &Context) -> Self {
+		Sun { config: cx.config.sun.clone(), block: cx.block.clone() }
+	}
+
+	fn render(&self) -> Format {
+		let (latitude, longitude) = match (self.config.latitude, self.config.longitude) {
+			(Some(latitude), Some(longitude)) => (latitude, longitude),
+			_ => return bfmt![text[""]],
+		};
+		let now = Utc::now();
+		let today = now.with_timezone(&Local).date_naive();
+		// The first sunrise or sunset still to come, looking at today and tomorrow
+		let next = [today, today + ChronoDuration::days(1)]
+			.iter()
+			.filter_map(|&date| sun::sunrise_sunset(date, latitude, longitude))
+			.flat_map(|(rise, set)| vec![("↑", rise), ("↓", set)])
+			.find(|(_, time)| *time > now);
+		let icon = self.block.icon(icons::SUN);
+		let mut text = match next {
+			Some((arrow, time)) => {
+				format!("{} {}{}", icon, arrow, time.with_timezone(&Local).format("%H:%M"))
+			}
+			// Polar day or night
+			None => String::from(icon),
+		};
+		if self.config.moon {
+			text.push(' ');
+			text.push_str(self.block.icon(moon_icon(moon_phase(now))));
+		}
+		self.block.apply(bfmt![fmt["{}", text]])
+	}
+}
+
+impl BarWidget for Sun {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		bar.add(Periodic::new(
+			Duration::from_secs(60),
+			staggered(self.block.schedule(), move || self.render()),
+		));
+	}
+}
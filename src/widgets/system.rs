@@ -0,0 +1,441 @@
+use std::{
+	collections::HashMap,
+	fs,
+	path::{Path, PathBuf},
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc, Mutex,
+	},
+	thread,
+};
+
+use unixbar::{
+	bfmt,
+	format::{ClickAction, MouseButton},
+	widget::Periodic,
+	Duration,
+};
+
+use super::{catch, clickable, details, staggered, Bar, BarWidget, Context};
+use crate::{
+	block::Block,
+	color::Color,
+	config::{DiskConfig, LoadConfig, TemperatureSensors, TemperatureUnit, Urgency},
+	icons::{self, Icon},
+	sampler::{Metric, Sampler},
+	samples::MovingAverage,
+	template,
+	threshold::{Alert, Direction, ThresholdNotifier},
+	units,
+};
+
+/// Creates the notifier for a configured alert threshold
+fn alert(
+	direction: Direction,
+	threshold: Option<f64>,
+	hysteresis: f64,
+	summary: &str,
+	icon: &str,
+) -> Option<ThresholdNotifier> {
+	let alert = Alert {
+		summary: String::from(summary),
+		body: String::new(),
+		icon: String::from(icon),
+		urgency: Urgency::Critical,
+		value_hint: false,
+	};
+	threshold.map(|threshold| ThresholdNotifier::new(direction, threshold, hysteresis, alert))
+}
+
+/// Shows the free space of the root filesystem
+pub struct Disk {
+	block: Block,
+	sampler: Sampler,
+	config: DiskConfig,
+	alert: Option<ThresholdNotifier>,
+}
+
+impl Disk {
+	pub fn new(cx: &Context) -> Self {
+		let threshold = cx.config.disk.alert;
+		cx.sampler.watch(Metric::Disk);
+		Disk {
+			block: cx.block.clone(),
+			sampler: cx.sampler.clone(),
+			config: cx.config.disk.clone(),
+			alert: alert(Direction::Below, threshold, 1.0, "Disk almost full", "drive-harddisk"),
+		}
+	}
+}
+
+impl BarWidget for Disk {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let sampler = self.sampler.clone();
+		bar.register_fn("disk_details", move || {
+			let show = || -> anyhow::Result<()> {
+				let snapshot = sampler.get();
+				let fs = snapshot.disk()?;
+				let (total, avail) = (fs.total.as_u64() as f64, fs.avail.as_u64() as f64);
+				let body =
+					format!("{} used of {}", units::bytes(total - avail), units::bytes(total));
+				details(&format!("Disk {}", fs.fs_mounted_on), &body)
+			};
+			show().unwrap_or(())
+		});
+		bar.add(Periodic::new(
+			Duration::from_secs(2),
+			staggered(self.block.schedule(), move || {
+				catch(&self.block, || {
+					let snapshot = self.sampler.get();
+					let fs = snapshot.disk()?;
+					if let Some(alert) = &self.alert {
+						alert.check(fs.avail.as_u64() as f64 / 1_000_000_000.0)?;
+					}
+					let avail = fs.avail.as_u64() as f64;
+					let (value, unit) =
+						units::scaled(avail, self.config.unit, self.config.precision);
+					let text = template::fill(
+						&self.config.format,
+						&[
+							("icon", &self.block.icon(icons::DISK)),
+							("value", &value),
+							("unit", &unit),
+						],
+					);
+					Ok(self.block.apply(bfmt![
+						click[MouseButton::Right => fn "disk_details"]
+						fg["#cccccc"]
+						fmt["{}", text]
+					]))
+				})
+			}),
+		));
+	}
+}
+
+/// Shows the one minute load average
+pub struct Load {
+	config: LoadConfig,
+	block: Block,
+	sampler: Sampler,
+	/// The number of CPU cores, to normalize the load with
+	cores: usize,
+	/// The smoothed 1, 5 and 15 minute averages
+	averages: Mutex<[MovingAverage; 3]>,
+}
+
+impl Load {
+	pub fn new(cx: &Context) -> Self {
+		cx.sampler.watch(Metric::Load);
+		Load {
+			config: cx.config.load.clone(),
+			block: cx.block.clone(),
+			sampler: cx.sampler.clone(),
+			cores: thread::available_parallelism().map_or(1, |cores| cores.get()),
+			averages: Mutex::new([
+				MovingAverage::new(cx.config.load.smoothing),
+				MovingAverage::new(cx.config.load.smoothing),
+				MovingAverage::new(cx.config.load.smoothing),
+			]),
+		}
+	}
+
+	/// The color of the widget for the 1 minute average `load`
+	fn color(&self, load: f32) -> &Color {
+		let config = &self.config;
+		match load / self.cores as f32 {
+			_ if !config.per_core_color => &config.color,
+			ratio if ratio > config.critical => &config.critical_color,
+			ratio if ratio > config.warning => &config.warning_color,
+			_ => &config.color,
+		}
+	}
+}
+
+impl BarWidget for Load {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		bar.add(Periodic::new(
+			Duration::from_secs(1),
+			staggered(self.block.schedule(), move || {
+				catch(&self.block, || {
+					let load = self.sampler.get().load()?.clone();
+					let [one, five, fifteen] = {
+						let mut averages = self.averages.lock().unwrap();
+						let mut smooth =
+							|i: usize, load: f32| averages[i].add(f64::from(load)) as f32;
+						[smooth(0, load.one), smooth(1, load.five), smooth(2, load.fifteen)]
+					};
+					let value = match self.config.all {
+						true => format!("{:.2} {:.2} {:.2}", one, five, fifteen),
+						false => format!("{:.2}", one),
+					};
+					let text = template::fill(
+						&self.config.format,
+						&[("icon", &self.block.icon(icons::LOAD)), ("value", &value)],
+					);
+					Ok(self.block.apply(bfmt![
+						fg[self.color(one).as_str()]
+						fmt["{}", text]
+					]))
+				})
+			}),
+		));
+	}
+}
+
+/// Shows the amount of free memory
+pub struct Memory {
+	block: Block,
+	sampler: Sampler,
+	format: String,
+	alert: Option<ThresholdNotifier>,
+}
+
+impl Memory {
+	pub fn new(cx: &Context) -> Self {
+		let threshold = cx.config.memory.alert;
+		cx.sampler.watch(Metric::Memory);
+		Memory {
+			block: cx.block.clone(),
+			sampler: cx.sampler.clone(),
+			format: cx.config.memory.format.clone(),
+			alert: alert(Direction::Below, threshold, 0.25, "Memory almost full", "dialog-warning"),
+		}
+	}
+}
+
+impl BarWidget for Memory {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let sampler = self.sampler.clone();
+		bar.register_fn("memory_details", move || {
+			let show = || -> anyhow::Result<()> {
+				let snapshot = sampler.get();
+				let memory = snapshot.memory()?;
+				let (total, free) = (memory.total.as_u64() as f64, memory.free.as_u64() as f64);
+				let body =
+					format!("{} used of {}", units::bytes(total - free), units::bytes(total));
+				details("Memory", &body)
+			};
+			show().unwrap_or(())
+		});
+		bar.add(Periodic::new(
+			Duration::from_secs(2),
+			staggered(self.block.schedule(), move || {
+				catch(&self.block, || {
+					let snapshot = self.sampler.get();
+					let memory = snapshot.memory()?;
+					let free = memory.free.as_u64() as f32 / 1_000_000_000.0;
+					if let Some(alert) = &self.alert {
+						alert.check(f64::from(free))?;
+					}
+					let value = format!("{:.1}", free);
+					let text = template::fill(
+						&self.format,
+						&[
+							("icon", &self.block.icon(icons::MEMORY)),
+							("value", &value),
+							("unit", &"G"),
+						],
+					);
+					Ok(self.block.apply(bfmt![
+						click[MouseButton::Right => fn "memory_details"]
+						fg["#ffc300"]
+						fmt["{}", text]
+					]))
+				})
+			}),
+		));
+	}
+}
+
+/// Shows the CPU temperature
+pub struct Temperature {
+	block: Block,
+	sampler: Sampler,
+	format: String,
+	unit: TemperatureUnit,
+	toggle_unit: bool,
+	/// Whether the unit has been switched by clicking the widget
+	toggled: Arc<AtomicBool>,
+	sensors: TemperatureSensors,
+	smoothing: usize,
+	/// The smoothed temperature by package id, `None` for the hottest package or the single
+	/// temperature
+	averages: Mutex<HashMap<Option<u32>, MovingAverage>>,
+	alert: Option<ThresholdNotifier>,
+}
+
+impl Temperature {
+	pub fn new(cx: &Context) -> Self {
+		let threshold = cx.config.temperature.alert;
+		cx.sampler.watch(Metric::Temperature);
+		Temperature {
+			block: cx.block.clone(),
+			sampler: cx.sampler.clone(),
+			format: cx.config.temperature.format.clone(),
+			unit: cx.config.temperature.unit,
+			toggle_unit: cx.config.temperature.toggle_unit,
+			toggled: Arc::default(),
+			sensors: cx.config.temperature.sensors,
+			smoothing: cx.config.temperature.smoothing,
+			averages: Mutex::default(),
+			alert: alert(
+				Direction::Above,
+				threshold,
+				5.0,
+				"CPU temperature high",
+				"dialog-warning",
+			),
+		}
+	}
+}
+
+impl Temperature {
+	/// Adds a reading of the sensor `id`. Returns its smoothed temperature.
+	fn smooth(&self, id: Option<u32>, temp: f32) -> f32 {
+		let mut averages = self.averages.lock().unwrap();
+		let average = averages.entry(id).or_insert_with(|| MovingAverage::new(self.smoothing));
+		average.add(f64::from(temp)) as f32
+	}
+
+	/// The unit shown: the configured one, or the other one after a click switched it
+	fn unit(&self) -> TemperatureUnit {
+		match self.toggled.load(Ordering::Relaxed) {
+			true => self.unit.toggled(),
+			false => self.unit,
+		}
+	}
+}
+
+impl BarWidget for Temperature {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let toggled = self.toggled.clone();
+		bar.register_fn("temp_unit", move || {
+			toggled.fetch_xor(true, Ordering::Relaxed);
+		});
+		bar.add(Periodic::new(
+			Duration::from_secs(2),
+			staggered(self.block.schedule(), move || {
+				catch(&self.block, || {
+					let unit = self.unit();
+					// Fall back to the single temperature without coretemp sensors
+					let packages = match self.sensors {
+						TemperatureSensors::Single => Vec::new(),
+						_ => packages(),
+					};
+					let temp = match packages.iter().map(|&(_, temp)| temp).reduce(f32::max) {
+						Some(temp) => temp,
+						None => self.sampler.get().temperature()?,
+					};
+					if let Some(alert) = &self.alert {
+						alert.check(f64::from(temp))?;
+					}
+					let (icon, value) = reading(self.smooth(None, temp), unit);
+					let icon = self.block.icon(icon);
+					let value = match self.sensors {
+						TemperatureSensors::All if !packages.is_empty() => packages
+							.iter()
+							.map(|(id, temp)| {
+								let (_, value) = reading(self.smooth(Some(*id), *temp), unit);
+								format!("P{} {}", id, value)
+							})
+							.collect::<Vec<_>>()
+							.join(" "),
+						_ => value,
+					};
+					let text = template::fill(
+						&self.format,
+						&[("icon", &icon), ("value", &value), ("unit", &unit.symbol())],
+					);
+					let format = bfmt![fg["#10ff10"] fmt["{}", text]];
+					let actions: &[_] = match self.toggle_unit {
+						true => &[(MouseButton::Left, "temp_unit")],
+						false => &[],
+					};
+					Ok(self.block.apply(clickable(format, actions)))
+				})
+			}),
+		));
+	}
+}
+
+/// The icon and the shown value for a temperature in °C. The icon thresholds are in °C like the
+/// alert threshold, only the shown value is converted to `unit`.
+fn reading(celsius: f32, unit: TemperatureUnit) -> (Icon, String) {
+	let level = match celsius as u32 {
+		0..=59 => 0,
+		60..=69 => 1,
+		70..=79 => 2,
+		80..=89 => 3,
+		_ => 4,
+	};
+	(icons::TEMPERATURE[level], format!("{:.1}", unit.from_celsius(celsius)))
+}
+
+/// The temperature in °C of every CPU package, by package id, read from the coretemp hwmon
+/// sensors. Empty if there are none, e.g. on AMD CPUs.
+fn packages() -> Vec<(u32, f32)> {
+	let mut packages = Vec::new();
+	let hwmons = match fs::read_dir("/sys/class/hwmon") {
+		Ok(hwmons) => hwmons,
+		Err(_) => return packages,
+	};
+	for hwmon in hwmons.filter_map(Result::ok).map(|entry| entry.path()) {
+		match fs::read_to_string(hwmon.join("name")) {
+			Ok(name) if name.trim() == "coretemp" => (),
+			_ => continue,
+		}
+		let sensors = match fs::read_dir(&hwmon) {
+			Ok(sensors) => sensors,
+			Err(_) => continue,
+		};
+		for sensor in sensors.filter_map(Result::ok) {
+			let file = sensor.file_name().to_string_lossy().into_owned();
+			let sensor = match file.strip_suffix("_label") {
+				Some(sensor) => sensor,
+				None => continue,
+			};
+			// Cores are labelled "Core N", packages "Package id N"
+			let label =
+				fs::read_to_string(sensor_path(&hwmon, sensor, "label")).unwrap_or_default();
+			let id = match label.trim().strip_prefix("Package id ").map(str::parse) {
+				Some(Ok(id)) => id,
+				_ => continue,
+			};
+			let input =
+				fs::read_to_string(sensor_path(&hwmon, sensor, "input")).unwrap_or_default();
+			if let Ok(millidegrees) = input.trim().parse::<f32>() {
+				packages.push((id, millidegrees / 1000.0));
+			}
+		}
+	}
+	packages.sort_by_key(|&(id, _)| id);
+	packages
+}
+
+fn sensor_path(hwmon: &Path, sensor: &str, file: &str) -> PathBuf {
+	hwmon.join(format!("{}_{}", sensor, file))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn temperature_thresholds_in_celsius() {
+		let cases = [(45.0, 0), (65.0, 1), (75.0, 2), (85.0, 3), (95.0, 4)];
+		for &(celsius, level) in &cases {
+			for &unit in &[TemperatureUnit::Celsius, TemperatureUnit::Fahrenheit] {
+				let (icon, _) = reading(celsius, unit);
+				assert_eq!(icon, icons::TEMPERATURE[level], "{} °C in {:?}", celsius, unit);
+			}
+		}
+	}
+
+	#[test]
+	fn temperature_value_in_unit() {
+		assert_eq!(reading(75.0, TemperatureUnit::Celsius).1, "75.0");
+		// 167 °F would be past the highest threshold if it were compared
+		assert_eq!(reading(75.0, TemperatureUnit::Fahrenheit).1, "167.0");
+	}
+}
@@ -0,0 +1,77 @@
+use std::{fs, path::Path, process::Command, time::Duration as StdDuration};
+
+use anyhow::anyhow;
+use unixbar::{bfmt, format::MouseButton, widget::Periodic, Duration};
+
+use super::{catch, staggered, Bar, BarWidget, Context};
+use crate::{
+	block::{self, Block},
+	cache::Cached,
+	config::{TaskSource, TasksConfig},
+	icons, process, template,
+};
+
+/// Shows the number of pending tasks
+pub struct Tasks {
+	config: TasksConfig,
+	block: Block,
+}
+
+impl Tasks {
+	pub fn new(cx: &Context) -> Self {
+		Tasks { config: cx.config.tasks.clone(), block: cx.block.clone() }
+	}
+}
+
+impl BarWidget for Tasks {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let (source, file) = (self.config.source, self.config.file.clone());
+		let interval = StdDuration::from_secs(self.config.interval_secs);
+		let count = Cached::spawn(interval, &self.block.schedule().heartbeat, move || {
+			let count = match (source, &file) {
+				(TaskSource::Taskwarrior, _) => taskwarrior(),
+				(TaskSource::File, Some(file)) => todo_txt(file),
+				(TaskSource::File, None) => Err(anyhow!("No todo.txt file configured")),
+			};
+			count.map_err(|e| e.to_string())
+		});
+		bar.add(Periodic::new(
+			Duration::from_secs(1),
+			staggered(self.block.schedule(), move || {
+				catch(&self.block, || {
+					let count = match count.get() {
+						Some(Ok(count)) => count,
+						Some(Err(e)) => return Err(anyhow!(e)),
+						None => return Ok(bfmt![text[""]]),
+					};
+					let text = template::fill(
+						&self.config.format,
+						&[("icon", &self.block.icon(icons::TASKS)), ("value", &count)],
+					);
+					let format = bfmt![fmt["{}", text]];
+					let format = match &self.config.on_click {
+						Some(command) => block::bind(format, &MouseButton::Left, command),
+						None => format,
+					};
+					Ok(self.block.apply(format))
+				})
+			}),
+		));
+	}
+}
+
+/// Counts the pending Taskwarrior tasks
+pub fn taskwarrior() -> anyhow::Result<usize> {
+	let output = process::output(Command::new("task").args(&["+PENDING", "count"]))?;
+	let stdout = String::from_utf8_lossy(&output.stdout);
+	stdout
+		.trim()
+		.parse()
+		.map_err(|_| anyhow!("task printed an unexpected count {:?}", stdout.trim()))
+}
+
+/// Counts the tasks in a todo.txt file that aren't marked as done
+pub fn todo_txt(path: &Path) -> anyhow::Result<usize> {
+	let text = fs::read_to_string(path)?;
+	Ok(text.lines().filter(|line| !line.trim().is_empty() && !line.starts_with("x ")).count())
+}
@@ -0,0 +1,149 @@
+use std::{
+	fs,
+	path::PathBuf,
+	sync::Mutex,
+	time::{Duration as StdDuration, Instant},
+};
+
+use anyhow::anyhow;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use systemstat::{Platform, System};
+use unixbar::{bfmt, format::Format, widget::Periodic, Duration};
+
+use super::{catch, network, staggered, Bar, BarWidget, Context};
+use crate::{
+	block::Block,
+	config::{TrafficConfig, TrafficPeriod},
+	dirs, icons, template, units,
+};
+
+/// How often the count is written to the cache directory
+const SAVE_INTERVAL: StdDuration = StdDuration::from_secs(60);
+
+/// The count kept in the cache directory
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Saved {
+	/// The day counted, as `YYYY-MM-DD`
+	date: String,
+	/// The bytes received and sent that day
+	bytes: u64,
+}
+
+/// The data transferred today
+#[derive(Debug)]
+struct Counter {
+	saved: Saved,
+	/// The interface and its byte counter at the last update
+	last: Option<(String, u64)>,
+	/// When the count was last written to the cache directory
+	written: Instant,
+}
+
+impl Counter {
+	/// Adds the data transferred since the last update, given the byte counter of `iface`.
+	/// Returns the bytes transferred today.
+	fn update(&mut self, iface: &str, counter: u64) -> u64 {
+		// Starts over at local midnight
+		let today = Local::now().date_naive().to_string();
+		if self.saved.date != today {
+			self.saved = Saved { date: today, bytes: 0 };
+		}
+		match &self.last {
+			// The kernel's counter starts over from 0 when the interface restarts
+			Some((name, last)) if name == iface => {
+				self.saved.bytes += counter.checked_sub(*last).unwrap_or(counter)
+			}
+			// The first counter of an interface only sets the baseline
+			_ => (),
+		}
+		self.last = Some((iface.to_owned(), counter));
+		self.saved.bytes
+	}
+}
+
+/// Shows the data received and sent over the network today, or since the interface came up
+pub struct Traffic {
+	config: TrafficConfig,
+	block: Block,
+	/// The interface to count, if configured
+	interface: Option<String>,
+	counter: Mutex<Counter>,
+}
+
+impl Traffic {
+	pub fn new(cx: &Context) -> Self {
+		let config = &cx.config.traffic;
+		let saved = match config.since {
+			TrafficPeriod::Midnight => load().unwrap_or_else(|e| {
+				eprintln!("Couldn't read the traffic count: {}", e);
+				Saved::default()
+			}),
+			TrafficPeriod::Boot => Saved::default(),
+		};
+		Traffic {
+			config: config.clone(),
+			block: cx.block.clone(),
+			interface: config.interface.clone().or_else(|| cx.config.network.interface.clone()),
+			counter: Mutex::new(Counter { saved, last: None, written: Instant::now() }),
+		}
+	}
+
+	fn render(&self) -> anyhow::Result<Format> {
+		let iface = match self.interface.clone().or_else(network::active_interface) {
+			Some(iface) => iface,
+			None => return Ok(bfmt![text[""]]),
+		};
+		let stats = System::new().network_stats(&iface)?;
+		let counter = stats.rx_bytes.as_u64() + stats.tx_bytes.as_u64();
+		let bytes = match self.config.since {
+			TrafficPeriod::Midnight => {
+				let mut state = self.counter.lock().unwrap();
+				let bytes = state.update(&iface, counter);
+				if state.written.elapsed() >= SAVE_INTERVAL {
+					state.written = Instant::now();
+					if let Err(e) = save(&state.saved) {
+						eprintln!("Couldn't save the traffic count: {}", e);
+					}
+				}
+				bytes
+			}
+			TrafficPeriod::Boot => counter,
+		};
+		let (value, unit) = units::scaled(bytes as f64, self.config.unit, self.config.precision);
+		let text = template::fill(
+			&self.config.format,
+			&[("icon", &self.block.icon(icons::TRAFFIC)), ("value", &value), ("unit", &unit)],
+		);
+		Ok(self.block.apply(bfmt![fg[self.config.color.as_str()] fmt["{}", text]]))
+	}
+}
+
+impl BarWidget for Traffic {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		bar.add(Periodic::new(
+			Duration::from_secs(5),
+			staggered(self.block.schedule(), move || catch(&self.block, || self.render())),
+		));
+	}
+}
+
+fn path() -> anyhow::Result<PathBuf> {
+	Ok(dirs::cache_dir()?.join("traffic.json"))
+}
+
+/// Reads the count saved by a previous run. A count from another day is discarded on the first
+/// update.
+fn load() -> anyhow::Result<Saved> {
+	let path = path()?;
+	if !path.exists() {
+		return Ok(Saved::default());
+	}
+	let contents = fs::read(&path)?;
+	serde_json::from_slice(&contents).map_err(|e| anyhow!("{}: {}", path.display(), e))
+}
+
+fn save(saved: &Saved) -> anyhow::Result<()> {
+	fs::write(path()?, serde_json::to_vec(saved)?)?;
+	Ok(())
+}
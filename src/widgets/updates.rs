@@ -0,0 +1,71 @@
+use std::{process::Command, time::Duration as StdDuration};
+
+use anyhow::anyhow;
+use unixbar::{bfmt, format::MouseButton, widget::Periodic, Duration};
+
+use super::{catch, staggered, Bar, BarWidget, Context};
+use crate::{
+	block::{self, Block},
+	cache::Cached,
+	config::UpdatesConfig,
+	icons, process, template,
+};
+
+/// Shows the number of available package updates
+pub struct Updates {
+	config: UpdatesConfig,
+	block: Block,
+}
+
+impl Updates {
+	pub fn new(cx: &Context) -> Self {
+		Updates { config: cx.config.updates.clone(), block: cx.block.clone() }
+	}
+}
+
+impl BarWidget for Updates {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let command = self.config.command.clone();
+		let interval = StdDuration::from_secs(self.config.interval_secs);
+		let heartbeat = self.block.schedule().heartbeat;
+		let count =
+			Cached::spawn(interval, &heartbeat, move || count(&command).map_err(|e| e.to_string()));
+		bar.add(Periodic::new(
+			Duration::from_secs(1),
+			staggered(self.block.schedule(), move || {
+				catch(&self.block, || {
+					let count = match count.get() {
+						Some(Ok(count)) => count,
+						Some(Err(e)) => return Err(anyhow!(e)),
+						None => 0,
+					};
+					if count == 0 && self.config.hide_empty {
+						return Ok(bfmt![text[""]]);
+					}
+					let text = template::fill(
+						&self.config.format,
+						&[("icon", &self.block.icon(icons::UPDATES)), ("value", &count)],
+					);
+					let format = bfmt![fmt["{}", text]];
+					let format = match &self.config.on_click {
+						Some(command) => block::bind(format, &MouseButton::Left, command),
+						None => format,
+					};
+					Ok(self.block.apply(format))
+				})
+			}),
+		));
+	}
+}
+
+/// Runs `command` through the shell, and counts the lines it prints, one per update
+pub fn count(command: &str) -> anyhow::Result<usize> {
+	let output = process::output(Command::new("sh").args(&["-c", command]))?;
+	// checkupdates exits with 2 when there are no updates
+	if !output.status.success() && output.status.code() != Some(2) {
+		let stderr = String::from_utf8_lossy(&output.stderr);
+		return Err(anyhow!("Couldn't check for updates: {}", stderr.trim()));
+	}
+	let stdout = String::from_utf8_lossy(&output.stdout);
+	Ok(stdout.lines().filter(|line| !line.trim().is_empty()).count())
+}
@@ -0,0 +1,185 @@
+use std::sync::{
+	atomic::{AtomicBool, Ordering},
+	Arc, Mutex,
+};
+
+#[cfg(feature = "alsa")]
+use unixbar::widget::{self, ALSA};
+use unixbar::{
+	bfmt,
+	format::{Format, MouseButton},
+};
+#[cfg(feature = "pulse")]
+use unixbar::{widget::Periodic, Duration};
+
+#[cfg(feature = "pulse")]
+use super::{catch, detach};
+use super::{clickable, lock_icon, report, staggered, Bar, BarWidget, Context};
+use crate::{
+	block::Block,
+	config::{GaugeConfig, LevelDisplay, VolumeBackend, VolumeConfig},
+	events::{Event, Events},
+	gauge, icons,
+	osd::Osd,
+	template, volume,
+};
+
+/// Shows the ALSA master volume, and controls the volume and output device
+pub struct Volume {
+	config: VolumeConfig,
+	gauge: GaugeConfig,
+	events: Events,
+	block: Block,
+	osd: Osd,
+	/// Whether scrolling is ignored
+	locked: Arc<AtomicBool>,
+	/// The last volume and mute state, to only report changes
+	last: Mutex<Option<(u8, bool)>>,
+	/// The devices the output device picker leaves out
+	#[cfg(feature = "pulse")]
+	device_menu: volume::DeviceMenu,
+}
+
+impl Volume {
+	pub fn new(cx: &Context) -> Self {
+		Volume {
+			config: cx.config.volume.clone(),
+			gauge: cx.config.gauge.clone(),
+			events: cx.events.clone(),
+			block: cx.block.clone(),
+			osd: Osd::new(&cx.config.osd, "Volume", "audio-volume-high"),
+			locked: Arc::new(AtomicBool::new(cx.config.volume.locked)),
+			last: Mutex::new(None),
+			#[cfg(feature = "pulse")]
+			device_menu: volume::DeviceMenu::new(&cx.config.device_menu),
+		}
+	}
+}
+
+impl Volume {
+	fn render(&self, volume: f32, muted: bool) -> Format {
+		let state = ((volume * 100.0).round() as u8, muted);
+		{
+			let mut last = self.last.lock().unwrap();
+			if *last != Some(state) {
+				*last = Some(state);
+				self.events.emit(Event::VolumeChanged { volume: state.0, muted: state.1 });
+			}
+		}
+		let icon = self.block.icon(volume::icon((volume * 100.0) as u8));
+		// Muting at a high volume still shows as a high volume, but struck through
+		let strike = muted && self.config.strike_muted;
+		let text = match (muted && !strike, self.config.decibels) {
+			(true, _) => {
+				let icon = self.block.icon(icons::MUTED);
+				template::fill(&self.config.muted_format, &[("icon", &icon)])
+			}
+			(false, true) => {
+				let value = match self.config.backend.db(volume) {
+					Some(db) => format!("{:.1}", db),
+					None => String::from("-∞"),
+				};
+				template::fill(&self.config.db_format, &[("icon", &icon), ("value", &value)])
+			}
+			(false, false) => {
+				let vol = volume * 100.0;
+				let value = format!("{:.*}", self.config.precision, vol);
+				template::fill(&self.config.format, &[("icon", &icon), ("value", &value)])
+			}
+		};
+		let lock = lock_icon(&self.block, &self.locked);
+		let leveled = !muted || strike;
+		let label = match (self.config.display, leveled) {
+			(LevelDisplay::Gauge, true) => format!("{}{}", lock, icon),
+			_ => format!("{}{}", lock, text),
+		};
+		let label = match strike {
+			true => struck(&label),
+			false => label,
+		};
+		let mut format = bfmt![fg["#9090ff"] fmt["{}", label]];
+		if leveled && self.config.display != LevelDisplay::Percent {
+			format = gauge::append(format, f64::from(volume), &self.gauge);
+		}
+		self.block.apply(clickable(
+			format,
+			&[
+				(MouseButton::ScrollDown, "vol_down"),
+				(MouseButton::ScrollUp, "vol_up"),
+				(MouseButton::Middle, "vol_mute"),
+				(MouseButton::Left, "device_menu"),
+				(MouseButton::Right, "vol_lock"),
+			],
+		))
+	}
+}
+
+impl BarWidget for Volume {
+	/// Hidden where there's no sound card, e.g. in containers
+	fn visible(&self) -> bool {
+		match self.config.backend.probe() {
+			Ok(()) => true,
+			Err(e) => {
+				eprintln!("No audio system found: {}", e);
+				false
+			}
+		}
+	}
+
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let backend = self.config.backend;
+		let (up, down) = (self.osd.clone(), self.osd.clone());
+		let (up_lock, down_lock, lock) =
+			(self.locked.clone(), self.locked.clone(), self.locked.clone());
+		bar.register_fn("vol_up", move || {
+			if !up_lock.load(Ordering::Relaxed) {
+				report("Couldn't change the volume", backend.add(5).map(|vol| up.show(vol)))
+			}
+		})
+		.register_fn("vol_down", move || {
+			if !down_lock.load(Ordering::Relaxed) {
+				report("Couldn't change the volume", backend.add(-5).map(|vol| down.show(vol)))
+			}
+		})
+		.register_fn("vol_lock", move || {
+			lock.fetch_xor(true, Ordering::Relaxed);
+		})
+		.register_fn("vol_mute", move || report("Couldn't mute", backend.mute()));
+		#[cfg(feature = "pulse")]
+		{
+			let device_menu = self.device_menu.clone();
+			bar.register_fn("device_menu", move || {
+				let device_menu = device_menu.clone();
+				detach("Couldn't change audio device", move || volume::menu(&device_menu))
+			});
+		}
+		match backend {
+			#[cfg(feature = "alsa")]
+			VolumeBackend::Alsa => {
+				bar.add(widget::Volume::new(ALSA::new(), move |volume| {
+					self.render(volume.volume, volume.muted)
+				}));
+			}
+			// PulseAudio has no change notifications through unixbar, so poll it
+			#[cfg(feature = "pulse")]
+			VolumeBackend::Pulse => {
+				bar.add(Periodic::new(
+					Duration::from_secs(1),
+					staggered(self.block.schedule(), move || {
+						catch(&self.block, || {
+							let (volume, muted) = volume::pulse_state()?;
+							Ok(self.render(volume, muted))
+						})
+					}),
+				));
+			}
+			#[allow(unreachable_patterns)]
+			_ => eprintln!("warning: {}", backend.unsupported()),
+		}
+	}
+}
+
+/// Strikes `text` through with combining overlays
+fn struck(text: &str) -> String {
+	text.chars().flat_map(|c| [c, '\u{336}']).collect()
+}
@@ -0,0 +1,62 @@
+use std::{
+	sync::{Arc, Mutex},
+	thread,
+	time::Duration as StdDuration,
+};
+
+use unixbar::{bfmt, format::Format, widget::Periodic, Duration};
+
+use super::{staggered, truncate, Bar, BarWidget, Context};
+use crate::{block::Block, config::WindowConfig, ipc::Connection};
+
+/// Shows the title of the focused i3 or sway window
+pub struct Window {
+	config: WindowConfig,
+	block: Block,
+	/// The title, updated whenever the window manager reports a change in focus or title
+	title: Arc<Mutex<Option<String>>>,
+}
+
+impl Window {
+	pub fn new(cx: &Context) -> Self {
+		Window { config: cx.config.window.clone(), block: cx.block.clone(), title: Arc::default() }
+	}
+
+	fn render(&self) -> Format {
+		match &*self.title.lock().unwrap() {
+			Some(title) => {
+				self.block.apply(bfmt![fmt["{}", truncate(title, self.config.max_length)]])
+			}
+			None => bfmt![text[""]],
+		}
+	}
+}
+
+impl BarWidget for Window {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		let title = self.title.clone();
+		thread::spawn(move || loop {
+			if let Err(e) = watch(&title) {
+				eprintln!("Lost the window manager connection: {}", e);
+			}
+			*title.lock().unwrap() = None;
+			thread::sleep(StdDuration::from_secs(5));
+		});
+		// The title is kept up to date by the watcher, so this only picks up its changes
+		bar.add(Periodic::new(
+			Duration::from_millis(100),
+			staggered(self.block.schedule(), move || self.render()),
+		));
+	}
+}
+
+/// Keeps `title` up to date with the window manager's window and workspace events. Switching to
+/// an empty workspace only causes a workspace event.
+fn watch(title: &Mutex<Option<String>>) -> anyhow::Result<()> {
+	let mut connection = Connection::open()?;
+	let mut events = Connection::open()?.subscribe(&["window", "workspace"])?;
+	loop {
+		*title.lock().unwrap() = connection.focused_title()?;
+		events.next()?;
+	}
+}
@@ -0,0 +1,106 @@
+use std::{
+	sync::{Arc, Mutex},
+	thread,
+	time::Duration as StdDuration,
+};
+
+use unixbar::{
+	bfmt,
+	format::{ClickAction, Format, MouseButton},
+	widget::Periodic,
+	Duration,
+};
+
+use super::{report, staggered, Bar, BarWidget, Context};
+use crate::{
+	block::Block,
+	config::WorkspacesConfig,
+	ipc::{self, Connection, Workspace},
+};
+
+/// How many workspaces can be clicked. Every one needs its own registered function.
+const SLOTS: usize = 20;
+
+/// Shows the i3 or sway workspaces, and switches to a workspace when it's clicked
+pub struct Workspaces {
+	config: WorkspacesConfig,
+	block: Block,
+	/// The workspaces, updated whenever the window manager reports a change
+	workspaces: Arc<Mutex<Vec<Workspace>>>,
+}
+
+impl Workspaces {
+	pub fn new(cx: &Context) -> Self {
+		Workspaces {
+			config: cx.config.workspaces.clone(),
+			block: cx.block.clone(),
+			workspaces: Arc::default(),
+		}
+	}
+
+	fn render(&self) -> Format {
+		let workspaces = self.workspaces.lock().unwrap();
+		let parts = workspaces
+			.iter()
+			.enumerate()
+			.map(|(slot, workspace)| {
+				let color = if workspace.urgent {
+					&self.config.urgent_color
+				} else if workspace.focused {
+					&self.config.focused_color
+				} else if workspace.visible {
+					&self.config.visible_color
+				} else {
+					&self.config.color
+				};
+				let format = bfmt![fg[color.as_str()] fmt[" {} ", workspace.name]];
+				match slot < SLOTS {
+					true => Format::Clickable(
+						ClickAction::Function(MouseButton::Left, format!("workspace_{}", slot)),
+						Box::new(format),
+					),
+					false => format,
+				}
+			})
+			.map(Box::new)
+			.collect();
+		self.block.apply(Format::Concat(parts))
+	}
+}
+
+impl BarWidget for Workspaces {
+	fn attach(self: Box<Self>, bar: &mut Bar) {
+		for slot in 0..SLOTS {
+			let workspaces = self.workspaces.clone();
+			bar.register_fn(&format!("workspace_{}", slot), move || {
+				let name = workspaces.lock().unwrap().get(slot).map(|ws| ws.name.clone());
+				if let Some(name) = name {
+					report("Couldn't switch workspace", ipc::switch(&name));
+				}
+			});
+		}
+		let workspaces = self.workspaces.clone();
+		thread::spawn(move || loop {
+			if let Err(e) = watch(&workspaces) {
+				eprintln!("Lost the window manager connection: {}", e);
+			}
+			workspaces.lock().unwrap().clear();
+			thread::sleep(StdDuration::from_secs(5));
+		});
+		// The list is kept up to date by the watcher, so this only picks up its changes
+		bar.add(Periodic::new(
+			Duration::from_millis(100),
+			staggered(self.block.schedule(), move || self.render()),
+		));
+	}
+}
+
+/// Keeps `workspaces` up to date with the window manager's workspace events
+fn watch(workspaces: &Mutex<Vec<Workspace>>) -> anyhow::Result<()> {
+	let mut connection = Connection::open()?;
+	let mut events = Connection::open()?.subscribe(&["workspace"])?;
+	loop {
+		*workspaces.lock().unwrap() = connection.workspaces()?;
+		events.next()?;
+	}
+}